@@ -1,8 +1,17 @@
+use crate::calendar::{BusDayConv, Calendar};
+use crate::curve::{self, ZeroCurve};
 use crate::date_handle;
+use crate::day_count::{AccrualPeriod, DayCount};
+use crate::find_root::find_root_in;
+use crate::float_rate::FloatCpn;
+use crate::inflation::Linker;
+use crate::lattice::HullWhite;
 use crate::xirr::xirr;
 use crate::xirr::xnpv;
-use chrono::NaiveDate;
+use chrono::{Duration, NaiveDate};
 use std::collections::BTreeMap;
+use std::ops::Bound;
+use std::rc::Rc;
 
 #[derive(Debug)]
 pub struct FixedBond {
@@ -11,6 +20,24 @@ pub struct FixedBond {
     redem_value: f64,
     cpn_rate: f64,
     cpn_freq: CpnFreq,
+    day_count: DayCount,
+    stub: Stub,
+    first_cpn_date: Option<NaiveDate>,
+    penultimate_cpn_date: Option<NaiveDate>,
+    calendar: Rc<Calendar>,
+    bdc: BusDayConv,
+    adjust_accrual: bool,
+    eom: bool,
+    amort: BTreeMap<NaiveDate, f64>,
+    calls: BTreeMap<NaiveDate, f64>,
+    puts: BTreeMap<NaiveDate, f64>,
+    float: Option<FloatCpn>,
+    cpn_schedule: BTreeMap<NaiveDate, f64>,
+    linker: Option<Linker>,
+    ex_cpn_days: i64,
+    ex_cpn_bizdays: bool,
+    settle_days: i64,
+    settle_bizdays: bool,
 }
 
 #[derive(Debug)]
@@ -18,6 +45,50 @@ pub struct BondVal {
     pub ytm: f64,
     pub macd: f64,
     pub modd: f64,
+    pub cvx: f64,
+    pub eff_cvx: f64,
+    pub dv01: f64,
+    pub pv01: f64,
+}
+
+#[derive(Debug)]
+pub struct BondPrice {
+    pub clean_price: f64,
+    pub dirty_price: f64,
+    pub accrued: f64,
+}
+
+#[derive(Debug)]
+pub struct BondOas {
+    pub oas: f64,
+    pub eff_dur: f64,
+    pub eff_cvx: f64,
+}
+
+// The total return over the horizon and its breakdown, all relative to the dirty price
+#[derive(Debug)]
+pub struct BondHorizon {
+    pub income: f64,
+    pub reinvest: f64,
+    pub roll_down: f64,
+    pub price_chg: f64,
+    pub total: f64,
+    // the clean price on the horizon date at the exit yield, if not matured
+    pub horizon_price: Option<f64>,
+}
+
+// The carry and roll-down over the horizon in price points, and the breakeven yield change
+#[derive(Debug)]
+pub struct BondCarry {
+    pub carry: f64,
+    pub roll_down: f64,
+    pub breakeven: f64,
+}
+
+#[derive(Debug)]
+pub struct BondDm {
+    pub dm: f64,
+    pub spread_dur: f64,
 }
 
 #[derive(Copy, Clone)]
@@ -30,6 +101,8 @@ pub enum BondCfType {
 #[derive(Debug)]
 pub struct Cashflow {
     data: BTreeMap<NaiveDate, f64>,
+    // the coupon paid on the date and its ex-coupon date, after which it goes to the prior holder
+    ex_cpns: BTreeMap<NaiveDate, (NaiveDate, f64)>,
 }
 impl Cashflow {
     fn size(&self) -> usize {
@@ -37,7 +110,10 @@ impl Cashflow {
     }
     fn new() -> Self {
         let data: BTreeMap<NaiveDate, f64> = BTreeMap::new();
-        return Self { data };
+        Self {
+            data,
+            ex_cpns: BTreeMap::new(),
+        }
     }
     pub fn dates(&self) -> Vec<NaiveDate> {
         self.data.keys().cloned().collect()
@@ -58,10 +134,105 @@ impl Cashflow {
         }
         for (k, v) in &self.data {
             if k > ref_date {
-                data.insert(*k, *v);
+                // the coupon traded ex is excluded, while the date is kept for the redemption
+                let v = match self.ex_cpns.get(k) {
+                    Some((ex_date, cpn)) if ex_date <= ref_date => v - cpn,
+                    _ => *v,
+                };
+                data.insert(*k, v);
+            }
+        }
+        Self {
+            data,
+            ex_cpns: BTreeMap::new(),
+        }
+    }
+}
+
+// Where the irregular coupon period is placed when the schedule doesn't fit
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Stub {
+    ShortFirst,
+    LongFirst,
+    ShortLast,
+    LongLast,
+}
+
+pub fn to_stub(stub: &str) -> Result<Stub, String> {
+    match stub.to_lowercase().as_str() {
+        "short_first" => Ok(Stub::ShortFirst),
+        "long_first" => Ok(Stub::LongFirst),
+        "short_last" => Ok(Stub::ShortLast),
+        "long_last" => Ok(Stub::LongLast),
+        _ => Err(format!("stub({}) is undefined", stub)),
+    }
+}
+
+// How the cashflows are discounted by the yield
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Convention {
+    // the annual effective IRR over the years between the dates, i.e., Excel's XIRR
+    Annual,
+    // compounded at the coupon frequency over the coupon periods
+    Compound,
+    // the same as `Compound` but the simple yield in the final coupon period, i.e., Excel's
+    // YIELD and the street convention
+    Street,
+    // continuously compounded over the years between the dates
+    Continuous,
+    // the valuation convention of CCDC (China Central Depository & Clearing) for the China
+    // interbank market, which compounds at the coupon frequency over the coupon periods (ACT/ACT
+    // by period), but uses the simple yield over the ACT/ACT years in the final coupon period
+    ChinaInterbank,
+}
+
+pub fn to_convention(convention: &str) -> Result<Convention, String> {
+    match convention.to_lowercase().as_str() {
+        "annual" => Ok(Convention::Annual),
+        "compound" => Ok(Convention::Compound),
+        "street" => Ok(Convention::Street),
+        "continuous" => Ok(Convention::Continuous),
+        "china_interbank" | "ccdc" => Ok(Convention::ChinaInterbank),
+        _ => Err(format!("convention({}) is undefined", convention)),
+    }
+}
+
+// The discounting of the cashflows under a yield convention. `times` are the years from the
+// reference date, which are the coupon periods over the frequency when compounding by period.
+struct YieldBasis {
+    conv: Convention,
+    freq: f64,
+    simple: bool,
+    times: Vec<f64>,
+}
+
+impl YieldBasis {
+    // The discount factor of `t` years by the yield `y`, and its 2nd derivative to `y`
+    fn df(&self, y: f64, t: f64) -> (f64, f64) {
+        if self.simple {
+            let df = 1.0 / (1.0 + y * t);
+            return (df, 2.0 * t * t * df.powi(3));
+        }
+        match self.conv {
+            Convention::Continuous => {
+                let df = (-y * t).exp();
+                (df, t * t * df)
+            }
+            _ => {
+                let f = self.freq;
+                (
+                    (1.0 + y / f).powf(-f * t),
+                    t * (t + 1.0 / f) * (1.0 + y / f).powf(-f * t - 2.0),
+                )
             }
         }
-        Self { data }
+    }
+    fn npv(&self, y: f64, cfs: &[f64]) -> f64 {
+        self.times
+            .iter()
+            .zip(cfs)
+            .map(|(t, cf)| cf * self.df(y, *t).0)
+            .sum()
     }
 }
 
@@ -79,6 +250,16 @@ fn to_cpn_freq(cpn_freq: i32) -> Result<CpnFreq, String> {
     }
 }
 
+// The value on the first date of `cfs`, keyed by the years from the date of `curve`, where the
+// zero rates are shifted by `shift(t)`
+fn curve_value<F>(cfs: &[(f64, f64)], curve: &ZeroCurve, shift: F) -> f64
+where
+    F: Fn(f64) -> f64,
+{
+    let df = |t: f64| (-(curve.zero(t) + shift(t)) * t).exp();
+    cfs[1..].iter().map(|(t, cf)| cf * df(*t)).sum::<f64>() / df(cfs[0].0)
+}
+
 impl FixedBond {
     pub fn new(
         value_date: NaiveDate,
@@ -93,111 +274,617 @@ impl FixedBond {
             redem_value,
             cpn_rate,
             cpn_freq: to_cpn_freq(cpn_freq)?,
+            day_count: DayCount::ActActIcma,
+            stub: Stub::ShortLast,
+            first_cpn_date: None,
+            penultimate_cpn_date: None,
+            calendar: Rc::new(Calendar::default()),
+            bdc: BusDayConv::Unadjusted,
+            adjust_accrual: false,
+            eom: false,
+            amort: BTreeMap::new(),
+            calls: BTreeMap::new(),
+            puts: BTreeMap::new(),
+            float: None,
+            cpn_schedule: BTreeMap::new(),
+            linker: None,
+            ex_cpn_days: 0,
+            ex_cpn_bizdays: true,
+            settle_days: 0,
+            settle_bizdays: true,
         })
     }
-    fn cpn_dates(&self, adjust: bool) -> Vec<NaiveDate> {
+    pub fn with_day_count(mut self, day_count: DayCount) -> Self {
+        self.day_count = day_count;
+        self
+    }
+    pub fn with_stub(mut self, stub: Stub) -> Self {
+        self.stub = stub;
+        self
+    }
+    pub fn with_first_cpn_date(
+        mut self,
+        first_cpn_date: Option<NaiveDate>,
+    ) -> Result<Self, String> {
+        if let Some(date) = first_cpn_date {
+            if date <= self.value_date || date > self.mty_date {
+                return Err(format!(
+                    "first_cpn_date({}) must be after value_date and not after mty_date",
+                    date
+                ));
+            }
+        }
+        self.first_cpn_date = first_cpn_date;
+        self.check_odd_cpn_dates()?;
+        Ok(self)
+    }
+    pub fn with_penultimate_cpn_date(
+        mut self,
+        penultimate_cpn_date: Option<NaiveDate>,
+    ) -> Result<Self, String> {
+        if let Some(date) = penultimate_cpn_date {
+            if date <= self.value_date || date >= self.mty_date {
+                return Err(format!(
+                    "penultimate_cpn_date({}) must be between value_date and mty_date",
+                    date
+                ));
+            }
+        }
+        self.penultimate_cpn_date = penultimate_cpn_date;
+        self.check_odd_cpn_dates()?;
+        Ok(self)
+    }
+    // The payment dates are adjusted by `bdc` on `calendar`. The accrual periods stay
+    // unadjusted unless `adjust_accrual` is true.
+    pub fn with_calendar(
+        mut self,
+        calendar: Rc<Calendar>,
+        bdc: BusDayConv,
+        adjust_accrual: bool,
+    ) -> Self {
+        self.calendar = calendar;
+        self.bdc = bdc;
+        self.adjust_accrual = adjust_accrual;
+        self
+    }
+    // When true, the coupon dates stick to month ends if the schedule starts from one
+    pub fn with_eom(mut self, eom: bool) -> Self {
+        self.eom = eom;
+        self
+    }
+    // The bond trades ex-coupon `days` (business days if `bizdays`) before each payment date,
+    // e.g., 7 business days for UK gilts. In the ex-coupon period the accrued is negative, and
+    // the next coupon goes to the prior holder.
+    pub fn with_ex_cpn(mut self, days: i64, bizdays: bool) -> Result<Self, String> {
+        if days < 0 {
+            return Err(format!("ex_cpn_days({}) must be non-negative", days));
+        }
+        self.ex_cpn_days = days;
+        self.ex_cpn_bizdays = bizdays;
+        Ok(self)
+    }
+    // The bond settles `days` (business days if `bizdays`) after the trade date, e.g., T+1
+    pub fn with_settlement(mut self, days: i64, bizdays: bool) -> Result<Self, String> {
+        if days < 0 {
+            return Err(format!("settle_days({}) must be non-negative", days));
+        }
+        self.settle_days = days;
+        self.settle_bizdays = bizdays;
+        Ok(self)
+    }
+    pub fn mty_date(&self) -> NaiveDate {
+        self.mty_date
+    }
+    pub fn cpn_rate(&self) -> f64 {
+        self.cpn_rate
+    }
+    // The coupon frequency, where 0 means the zero coupon bond
+    pub fn cpn_freq(&self) -> i32 {
+        match self.cpn_freq {
+            CpnFreq::Regular(freq) => freq,
+            CpnFreq::Zero => 0,
+        }
+    }
+    // The settlement date of the trade on `trade_date`, from which the accrued interest and
+    // the remaining cashflows are counted
+    pub fn settle_date(&self, trade_date: &NaiveDate) -> NaiveDate {
+        if self.settle_bizdays {
+            self.calendar.add_bizdays(trade_date, self.settle_days)
+        } else {
+            *trade_date + Duration::days(self.settle_days)
+        }
+    }
+    // The principal is repaid by `amort` (date -> amount) and the rest at `mty_date`
+    pub fn with_amortization(mut self, amort: BTreeMap<NaiveDate, f64>) -> Result<Self, String> {
+        for (date, value) in &amort {
+            if date <= &self.value_date || date > &self.mty_date {
+                return Err(format!(
+                    "amortization date({}) must be after value_date and not after mty_date",
+                    date
+                ));
+            }
+            if *value < 0.0 {
+                return Err(format!(
+                    "amortization value({}) must not be negative",
+                    value
+                ));
+            }
+        }
+        if amort.values().sum::<f64>() > self.redem_value + 1e-8 {
+            return Err("amortization exceeds redem_value".to_string());
+        }
+        self.amort = amort;
+        Ok(self)
+    }
+    // The coupon rate steps to the rate of `cpn_schedule` (effective date -> rate) from each
+    // effective date, while `cpn_rate` applies before the first one
+    pub fn with_cpn_schedule(
+        mut self,
+        cpn_schedule: BTreeMap<NaiveDate, f64>,
+    ) -> Result<Self, String> {
+        for date in cpn_schedule.keys() {
+            if date <= &self.value_date || date >= &self.mty_date {
+                return Err(format!(
+                    "coupon effective date({}) must be between value_date and mty_date",
+                    date
+                ));
+            }
+        }
+        self.cpn_schedule = cpn_schedule;
+        Ok(self)
+    }
+    // The principal and coupons are scaled by the index ratio if linked to inflation
+    pub fn with_linker(mut self, linker: Option<Linker>) -> Result<Self, String> {
+        if let Some(base_cpi) = linker.as_ref().and_then(|v| v.base_cpi) {
+            if !base_cpi.is_finite() || base_cpi <= 0.0 {
+                return Err(format!("base_cpi({}) must be positive", base_cpi));
+            }
+        }
+        self.linker = linker;
+        Ok(self)
+    }
+    // The floating coupon replaces `cpn_rate` if provided, which makes the bond an FRN
+    pub fn with_float(mut self, float: Option<FloatCpn>) -> Result<Self, String> {
+        if let Some(months) = float.as_ref().and_then(|v| v.reset_months) {
+            if months <= 0 {
+                return Err(format!("reset_months({}) must be positive", months));
+            }
+        }
+        self.float = float;
+        Ok(self)
+    }
+    // The issuer may call the bond on each date of `calls` (date -> call price per 100 face value)
+    pub fn with_calls(mut self, calls: BTreeMap<NaiveDate, f64>) -> Result<Self, String> {
+        self.check_option_dates(&calls, "call")?;
+        self.calls = calls;
+        Ok(self)
+    }
+    // The holder may put the bond on each date of `puts` (date -> put price per 100 face value)
+    pub fn with_puts(mut self, puts: BTreeMap<NaiveDate, f64>) -> Result<Self, String> {
+        self.check_option_dates(&puts, "put")?;
+        self.puts = puts;
+        Ok(self)
+    }
+    fn check_option_dates(
+        &self,
+        schedule: &BTreeMap<NaiveDate, f64>,
+        var: &str,
+    ) -> Result<(), String> {
+        for date in schedule.keys() {
+            if date <= &self.value_date || date >= &self.mty_date {
+                return Err(format!(
+                    "{} date({}) must be between value_date and mty_date",
+                    var, date
+                ));
+            }
+        }
+        Ok(())
+    }
+    // The outstanding notional after the principal repaid on or before `ref_date`
+    fn outstanding(&self, ref_date: &NaiveDate) -> f64 {
+        self.redem_value - self.amort.range(..=*ref_date).map(|(_, v)| v).sum::<f64>()
+    }
+    fn check_odd_cpn_dates(&self) -> Result<(), String> {
+        if let (Some(first), Some(penultimate)) = (self.first_cpn_date, self.penultimate_cpn_date) {
+            if first > penultimate {
+                return Err(format!(
+                    "first_cpn_date({}) must not be after penultimate_cpn_date({})",
+                    first, penultimate
+                ));
+            }
+        }
+        Ok(())
+    }
+    // The coupon periods from `value_date` to `mty_date`. By default, the schedule rolls
+    // forward from `value_date` so any irregular period ends at `mty_date`. Otherwise, it
+    // rolls backward from `mty_date` (or `penultimate_cpn_date`, to `first_cpn_date` if given)
+    // and the first period is the irregular one. Each period carries the regular coupon date
+    // (`ref_end`) that ACT/ACT ICMA measures the notional periods from.
+    fn cpn_periods(&self) -> Vec<AccrualPeriod> {
+        let months = self.cpn_months();
+        let mut periods: Vec<AccrualPeriod> = Vec::new();
+        if self.value_date >= self.mty_date {
+            return periods;
+        }
+        let (dates, odd_last) = match self.cpn_freq {
+            CpnFreq::Zero => (vec![self.value_date, self.mty_date], false),
+            CpnFreq::Regular(_) => {
+                let forward = self.first_cpn_date.is_none()
+                    && self.penultimate_cpn_date.is_none()
+                    && matches!(self.stub, Stub::ShortLast | Stub::LongLast);
+                if forward {
+                    (self.forward_cpn_dates(), true)
+                } else {
+                    (
+                        self.backward_cpn_dates(),
+                        self.penultimate_cpn_date.is_some(),
+                    )
+                }
+            }
+        };
+        let adjust = |i: usize| {
+            if self.adjust_accrual && i > 0 {
+                self.calendar.adjust(&dates[i], self.bdc)
+            } else {
+                dates[i]
+            }
+        };
+        for (i, w) in dates.windows(2).enumerate() {
+            // the notional periods of an odd last period roll forward from its start
+            let ref_end = if odd_last && i == dates.len() - 2 {
+                let mut k = 1;
+                loop {
+                    let date = date_handle::roll_months(&w[0], months * k, self.eom);
+                    if date >= w[1] {
+                        break date;
+                    }
+                    k += 1;
+                }
+            } else {
+                w[1]
+            };
+            periods.push(AccrualPeriod {
+                start: adjust(i),
+                end: adjust(i + 1),
+                ref_end,
+                months,
+                eom: self.eom,
+                mty_date: self.mty_date,
+            });
+        }
+        periods
+    }
+    fn forward_cpn_dates(&self) -> Vec<NaiveDate> {
+        let months = self.cpn_months();
         let mut dates: Vec<NaiveDate> = vec![self.value_date];
-        let mut ref_date = self.value_date;
-        loop {
-            match self.nxt_cpn_date(&ref_date, adjust) {
-                Some(date) => {
-                    ref_date = date;
+        let mut date = self.value_date;
+        let has_stub = loop {
+            date = date_handle::roll_months(&date, months, self.eom);
+            if date >= self.mty_date {
+                break date > self.mty_date;
+            }
+            dates.push(date);
+        };
+        if self.stub == Stub::LongLast && has_stub && dates.len() > 1 {
+            dates.pop();
+        }
+        dates.push(self.mty_date);
+        dates
+    }
+    fn backward_cpn_dates(&self) -> Vec<NaiveDate> {
+        let months = self.cpn_months();
+        let anchor = self.penultimate_cpn_date.unwrap_or(self.mty_date);
+        let stop = self.first_cpn_date.unwrap_or(self.value_date);
+        let mut dates: Vec<NaiveDate> = vec![anchor];
+        let mut k = 1;
+        let has_stub = loop {
+            let date = date_handle::roll_months(&anchor, -months * k, self.eom);
+            if date <= stop {
+                break date < stop;
+            }
+            dates.push(date);
+            k += 1;
+        };
+        match self.first_cpn_date {
+            Some(date) => {
+                if date < *dates.last().unwrap() {
                     dates.push(date);
                 }
-                None => break,
+            }
+            None => {
+                if self.stub == Stub::LongFirst && has_stub && dates.len() > 1 {
+                    dates.pop();
+                }
             }
         }
+        dates.push(self.value_date);
+        dates.reverse();
+        if anchor < self.mty_date {
+            dates.push(self.mty_date);
+        }
         dates
     }
-    // Calculate the Next Coupon Date
-    // @param adjust when true, it unadjust the last coupon date to mty date, if it's beyond
-    fn nxt_cpn_date(&self, ref_date: &NaiveDate, adjust: bool) -> Option<NaiveDate> {
-        if ref_date >= &self.mty_date {
-            return None;
+    // The length of the regular coupon period in months. A zero coupon bond
+    // is treated as accruing over annual notional periods.
+    fn cpn_months(&self) -> i32 {
+        match self.cpn_freq {
+            CpnFreq::Regular(i) => 12 / i,
+            CpnFreq::Zero => 12,
         }
-        let res = match self.cpn_freq {
-            CpnFreq::Regular(i) => Some(date_handle::add_months(ref_date, 12 / i as i32)),
-            CpnFreq::Zero => Some(self.mty_date),
-        };
-        match res {
-            Some(date) => {
-                if date > self.mty_date && adjust {
-                    Some(self.mty_date)
-                } else {
-                    Some(date)
+    }
+    // The coupon accrued from the beginning of `period` to `ref_date`. It accrues on the
+    // outstanding notional, which changes when the principal is repaid within the period.
+    // The coupon rate may also change within the period, e.g., when the floating index resets.
+    fn accrued_in_period(&self, period: &AccrualPeriod, ref_date: &NaiveDate) -> f64 {
+        let mut dates: Vec<NaiveDate> = self
+            .amort
+            .keys()
+            .cloned()
+            .chain(self.cpn_schedule.keys().cloned())
+            .chain(self.rate_resets(period))
+            .filter(|date| date > &period.start && date < ref_date)
+            .collect();
+        dates.sort();
+        dates.dedup();
+        let mut value = 0.0;
+        let mut start = period.start;
+        for date in dates {
+            value += self.outstanding(&start)
+                * self.cpn_rate_on(&start, period)
+                * self.day_count.year_frac(&start, &date, period);
+            start = date;
+        }
+        value
+            + self.outstanding(&start)
+                * self.cpn_rate_on(&start, period)
+                * self.day_count.year_frac(&start, ref_date, period)
+    }
+    // The dates within `period` (after its start) when the floating index resets
+    fn rate_resets(&self, period: &AccrualPeriod) -> Vec<NaiveDate> {
+        let mut out: Vec<NaiveDate> = Vec::new();
+        if let Some(months) = self.float.as_ref().and_then(|v| v.reset_months) {
+            for k in 1.. {
+                let date = date_handle::roll_months(&period.start, months * k, self.eom);
+                if date >= period.end {
+                    break;
                 }
+                out.push(date);
             }
-            None => None,
         }
+        out
     }
-    fn cpn_value(&self) -> f64 {
-        let factor = match self.cpn_freq {
-            CpnFreq::Regular(i) => 1.0 / i as f64,
-            CpnFreq::Zero => date_handle::year_frac(&self.mty_date, &self.value_date),
-        };
-        self.redem_value * self.cpn_rate * factor
+    // The coupon rate accruing on `date` within `period`. For the FRN, it's the index rate
+    // fixed `lookback` business days before the latest reset, plus the spread.
+    fn cpn_rate_on(&self, date: &NaiveDate, period: &AccrualPeriod) -> f64 {
+        match &self.float {
+            None => self
+                .cpn_schedule
+                .range(..=*date)
+                .next_back()
+                .map_or(self.cpn_rate, |(_, rate)| *rate),
+            Some(float) => {
+                let resets = self.rate_resets(period);
+                let reset = resets
+                    .iter()
+                    .rfind(|reset| *reset <= date)
+                    .unwrap_or(&period.start);
+                let next = resets
+                    .iter()
+                    .find(|reset| *reset > date)
+                    .unwrap_or(&period.end);
+                let fixing_date = self.calendar.add_bizdays(reset, -float.lookback);
+                float.index.rate(&fixing_date, reset, next) + float.spread
+            }
+        }
     }
     // Calculate the accrued coupon
     // `eod` means it returns the value at the end of the day.
     // If true, at the coupon / mty date it returns 0 otherwise returns the paying coupon at that day.
-    // It uses the Actual / Actual rule to calculate the accrued coupon.
-    fn accrued(&self, ref_date: &NaiveDate, eod: bool) -> f64 {
-        if ref_date > &self.mty_date || ref_date <= &self.value_date {
-            return 0.0;
+    // It uses the bond's day count convention to calculate the accrued coupon.
+    pub fn accrued(&self, ref_date: &NaiveDate, eod: bool) -> f64 {
+        let periods = self.cpn_periods();
+        match periods.last() {
+            Some(last) if ref_date <= &last.end && ref_date > &self.value_date => {}
+            _ => return 0.0,
         }
-        if eod && ref_date == &self.mty_date {
+        // the period that `start < ref_date <= end`
+        let i = periods.partition_point(|p| p.end < *ref_date);
+        let period = &periods[i];
+        // at the cpn date, the coupon has been paid then should be zero
+        if eod && ref_date == &period.end {
             return 0.0;
         }
-        let cpn_dates = self.cpn_dates(false);
-        let calculate = |i: usize| {
-            let last_cpn_date = cpn_dates[i - 1];
-            let nxt_cpn_date = cpn_dates[i];
-            let cpn_days = nxt_cpn_date.signed_duration_since(last_cpn_date).num_days();
-            let days = ref_date.signed_duration_since(last_cpn_date).num_days();
-            self.cpn_value() / cpn_days as f64 * days as f64
-        };
-
-        match cpn_dates.binary_search(&ref_date) {
-            // when ok, it means it's one of the cpn date and the coupon has been paid then should be zero
-            Ok(i) => {
-                if eod {
-                    0.0
-                } else {
-                    calculate(i)
-                }
+        let accrued = self.accrued_in_period(period, ref_date);
+        match self.ex_cpn_date(period) {
+            Some(ex_date) if ref_date >= &ex_date => {
+                accrued - self.accrued_in_period(period, &period.end)
             }
-            Err(i) => calculate(i),
+            _ => accrued,
         }
     }
-    fn dirty_price(&self, ref_date: &NaiveDate, clean_price: f64) -> f64 {
-        clean_price + self.accrued(ref_date, true)
+    // The date from which the coupon of `period` is traded ex, if any
+    fn ex_cpn_date(&self, period: &AccrualPeriod) -> Option<NaiveDate> {
+        if self.ex_cpn_days == 0 {
+            return None;
+        }
+        let pay_date = self.pay_date(period);
+        Some(if self.ex_cpn_bizdays {
+            self.calendar.add_bizdays(&pay_date, -self.ex_cpn_days)
+        } else {
+            pay_date - Duration::days(self.ex_cpn_days)
+        })
+    }
+    // The ratio of the outstanding notional to `redem_value`. The clean price is quoted
+    // on the original face value, so it's scaled by this factor for amortizing bonds.
+    fn factor(&self, ref_date: &NaiveDate) -> f64 {
+        self.outstanding(ref_date) / self.redem_value
+    }
+    pub fn dirty_price(&self, ref_date: &NaiveDate, clean_price: f64) -> f64 {
+        clean_price * self.factor(ref_date) + self.accrued(ref_date, true)
+    }
+    // The inverse of `dirty_price()`
+    pub fn clean_price(&self, ref_date: &NaiveDate, dirty_price: f64) -> f64 {
+        (dirty_price - self.accrued(ref_date, true)) / self.factor(ref_date)
     }
+    fn pay_date(&self, period: &AccrualPeriod) -> NaiveDate {
+        self.calendar.adjust(&period.end, self.bdc)
+    }
+    // The (accrual end date, payment date) of each coupon period
+    pub fn pay_schedule(&self) -> Vec<(NaiveDate, NaiveDate)> {
+        self.cpn_periods()
+            .iter()
+            .map(|period| (period.end, self.pay_date(period)))
+            .collect()
+    }
+    // The cashflows are keyed by the (adjusted) payment dates. The amortization dates are
+    // always included, so that the coupon and the redemption cashflows share the same dates.
     pub fn cashflow(&self, cftype: BondCfType) -> Cashflow {
-        let mut ref_date = self.nxt_cpn_date(&self.value_date, true);
         let mut res: Cashflow = Cashflow::new();
-        loop {
-            match ref_date {
-                Some(date) => {
-                    let redem: f64 = if date == self.mty_date {
-                        self.redem_value
-                    } else {
-                        0.0
-                    };
-                    let cpn = self.accrued(&date, false);
-                    let value = match cftype {
-                        BondCfType::Coupon => cpn,
-                        BondCfType::Redem => redem,
-                        BondCfType::All => cpn + redem,
-                    };
-                    res.data.insert(date, value);
-                    ref_date = self.nxt_cpn_date(&date, true);
-                }
-                None => break,
+        let periods = self.cpn_periods();
+        if periods.is_empty() {
+            return res;
+        }
+        for (date, value) in &self.amort {
+            let value = match cftype {
+                BondCfType::Coupon => 0.0,
+                BondCfType::Redem | BondCfType::All => *value,
+            };
+            *res.data
+                .entry(self.calendar.adjust(date, self.bdc))
+                .or_insert(0.0) += value;
+        }
+        for (i, period) in periods.iter().enumerate() {
+            let redem: f64 = if i == periods.len() - 1 {
+                self.redem_value - self.amort.values().sum::<f64>()
+            } else {
+                0.0
+            };
+            let cpn = self.accrued_in_period(period, &period.end);
+            if let (Some(ex_date), BondCfType::Coupon | BondCfType::All) =
+                (self.ex_cpn_date(period), cftype)
+            {
+                res.ex_cpns.insert(self.pay_date(period), (ex_date, cpn));
             }
+            let value = match cftype {
+                BondCfType::Coupon => cpn,
+                BondCfType::Redem => redem,
+                BondCfType::All => cpn + redem,
+            };
+            *res.data.entry(self.pay_date(period)).or_insert(0.0) += value;
         }
         res
     }
-    pub fn result(&self, ref_date: &NaiveDate, clean_price: f64) -> Option<BondVal> {
+    // The timing of the cashflows on `dates` under `conv`. Compounding by period, a date is
+    // placed in the coupon periods by the days, e.g., 1.5 periods away for the middle of the
+    // next period, and the payment dates are measured by their accrual end dates.
+    fn yield_basis(
+        &self,
+        ref_date: &NaiveDate,
+        dates: &[NaiveDate],
+        conv: Convention,
+    ) -> YieldBasis {
+        let periods = self.cpn_periods();
+        let accrual_dates: BTreeMap<NaiveDate, NaiveDate> = self
+            .pay_schedule()
+            .into_iter()
+            .map(|(accrual_date, pay_date)| (pay_date, accrual_date))
+            .collect();
+        let accrual_date = |date: &NaiveDate| *accrual_dates.get(date).unwrap_or(date);
+        let final_period = periods.last().map_or(true, |last| ref_date >= &last.start);
+        match (conv, &self.cpn_freq) {
+            (Convention::Compound | Convention::Street, CpnFreq::Regular(freq))
+            | (Convention::ChinaInterbank, CpnFreq::Regular(freq))
+                if !periods.is_empty() && (conv != Convention::ChinaInterbank || !final_period) =>
+            {
+                let position = |date: &NaiveDate| {
+                    let i = periods
+                        .partition_point(|p| p.end < *date)
+                        .min(periods.len() - 1);
+                    let period = &periods[i];
+                    i as f64
+                        + (*date - period.start).num_days() as f64
+                            / (period.end - period.start).num_days() as f64
+                };
+                let start = position(ref_date);
+                let freq = *freq as f64;
+                YieldBasis {
+                    conv,
+                    freq,
+                    simple: conv == Convention::Street && final_period,
+                    times: dates
+                        .iter()
+                        .map(|date| (position(&accrual_date(date)) - start) / freq)
+                        .collect(),
+                }
+            }
+            // CCDC measures the years by the interest years rolling backward from the maturity,
+            // i.e., ACT/ACT by year, and discounts by the simple yield within the final coupon
+            // period (or the final year of the zero coupon bond)
+            (Convention::ChinaInterbank, _) => {
+                let years_to_mty = |date: &NaiveDate| {
+                    let anniversary = |m: i32| date_handle::add_months(&self.mty_date, -12 * m);
+                    let mut m = 0;
+                    while anniversary(m + 1) >= *date {
+                        m += 1;
+                    }
+                    m as f64
+                        + (anniversary(m) - *date).num_days() as f64
+                            / (anniversary(m) - anniversary(m + 1)).num_days() as f64
+                };
+                let start = years_to_mty(ref_date);
+                YieldBasis {
+                    conv,
+                    freq: 1.0,
+                    simple: matches!(self.cpn_freq, CpnFreq::Regular(_)) || start <= 1.0,
+                    times: dates
+                        .iter()
+                        .map(|date| start - years_to_mty(&accrual_date(date)))
+                        .collect(),
+                }
+            }
+            _ => {
+                let times: Vec<f64> = dates
+                    .iter()
+                    .map(|date| date_handle::year_frac(date, ref_date))
+                    .collect();
+                // the zero coupon bond is in its final period within one year
+                let simple = conv == Convention::Street
+                    && times.iter().fold(0.0f64, |a, b| a.max(*b)) <= 1.0;
+                YieldBasis {
+                    conv,
+                    freq: 1.0,
+                    simple,
+                    times,
+                }
+            }
+        }
+    }
+    // The yield in `conv` of `cashflow`, which starts with the dirty price paid on `ref_date`
+    fn cashflow_yield(
+        &self,
+        ref_date: &NaiveDate,
+        cashflow: &Cashflow,
+        conv: Convention,
+    ) -> Option<f64> {
+        let dates = cashflow.dates();
+        let cfs = cashflow.values();
+        match conv {
+            Convention::Annual => xirr(&cfs, &dates, None).ok(),
+            _ => {
+                let basis = self.yield_basis(ref_date, &dates, conv);
+                let guess = xirr(&cfs, &dates, None).unwrap_or(0.0);
+                find_root_in(guess, -0.99, 10.0, |y| basis.npv(y, &cfs))
+            }
+        }
+    }
+    // The yield and the risk measures given the clean price, where the yield and the modified
+    // duration follow `conv`
+    pub fn result(
+        &self,
+        ref_date: &NaiveDate,
+        clean_price: f64,
+        conv: Convention,
+    ) -> Option<BondVal> {
         let dirty_price = self.dirty_price(ref_date, clean_price);
         let cashflow = self
             .cashflow(BondCfType::All)
@@ -205,39 +892,496 @@ impl FixedBond {
         if cashflow.len() == 0 {
             return None; // otherwise xirr will throw
         }
+        let ytm = self.cashflow_yield(ref_date, &cashflow, conv)?;
         let dates = cashflow.dates();
         let cfs = cashflow.values();
-        let ytm = xirr(&cfs, &dates, None).ok()?;
+        let basis = self.yield_basis(ref_date, &dates, conv);
+        let npv = |rate: f64| match conv {
+            Convention::Annual => xnpv(rate, &cfs, &dates).ok(),
+            _ => Some(basis.npv(rate, &cfs)),
+        };
         let modd = {
             let ytm_chg = 1e-6;
-            let npv1 = xnpv(ytm + ytm_chg, &cfs, &dates).ok()?;
-            let npv0 = xnpv(ytm - ytm_chg, &cfs, &dates).ok()?;
+            let npv1 = npv(ytm + ytm_chg)?;
+            let npv0 = npv(ytm - ytm_chg)?;
             -(npv1 - npv0) / (2.0 * ytm_chg * dirty_price)
         };
-        let macd = {
-            let cf2 = self
-                .cashflow(BondCfType::All)
-                .cf(ref_date, Some(dirty_price));
-            let years: Vec<f64> = cf2
-                .data
-                .keys()
-                .map(|date: &NaiveDate| date_handle::year_frac(date, ref_date))
-                .collect();
-            let macd = &years
+        let macd = basis
+            .times
+            .iter()
+            .zip(&cfs)
+            .map(|(t, cf)| cf * t * basis.df(ytm, *t).0)
+            .sum::<f64>()
+            / dirty_price;
+        let cvx = basis
+            .times
+            .iter()
+            .zip(&cfs)
+            .map(|(t, cf)| cf * basis.df(ytm, *t).1)
+            .sum::<f64>()
+            / dirty_price;
+        // a 1bp bump, as the 2nd order difference is too noisy with the tiny bump above
+        let bp = 1e-4;
+        let npv_mid = npv(ytm)?;
+        let npv_up = npv(ytm + bp)?;
+        let npv_down = npv(ytm - bp)?;
+        let eff_cvx = (npv_up + npv_down - 2.0 * npv_mid) / (dirty_price * bp * bp);
+        // both are measured per 100 face value
+        let face = 100.0 / self.outstanding(ref_date);
+        let dv01 = modd * dirty_price * bp * face;
+        let pv01 = (npv_mid - npv_up) * face;
+        Some(BondVal {
+            ytm,
+            macd,
+            modd,
+            cvx,
+            eff_cvx,
+            dv01,
+            pv01,
+        })
+    }
+    // The cashflows when the bond is called on `call_date`, where the issuer pays the call price
+    // of the outstanding principal plus the accrued coupon. The cashflows are truncated on the
+    // accrual schedule, i.e., the coupons of the periods ending on or before `call_date` are paid
+    // in full on their (adjusted) payment dates, and so is the call on the adjusted call date.
+    fn call_cashflow(&self, call_date: &NaiveDate, call_price: f64) -> Cashflow {
+        let mut res = Cashflow::new();
+        for (date, value) in self.amort.range(..=*call_date) {
+            *res.data
+                .entry(self.calendar.adjust(date, self.bdc))
+                .or_insert(0.0) += value;
+        }
+        for period in self.cpn_periods().iter().filter(|p| p.end <= *call_date) {
+            let cpn = self.accrued_in_period(period, &period.end);
+            if let Some(ex_date) = self.ex_cpn_date(period) {
+                res.ex_cpns.insert(self.pay_date(period), (ex_date, cpn));
+            }
+            *res.data.entry(self.pay_date(period)).or_insert(0.0) += cpn;
+        }
+        *res.data
+            .entry(self.calendar.adjust(call_date, self.bdc))
+            .or_insert(0.0) +=
+            self.outstanding(call_date) * call_price / 100.0 + self.cum_accrued(call_date);
+        res
+    }
+    // The accrued coupon the issuer pays when the bond is called or put on `date`, which is
+    // always cum-coupon, i.e., the ex-coupon period doesn't apply
+    fn cum_accrued(&self, date: &NaiveDate) -> f64 {
+        self.cpn_periods()
+            .iter()
+            .find(|p| p.start < *date && *date < p.end)
+            .map_or(0.0, |period| self.accrued_in_period(period, date))
+    }
+    // The yield in `conv` to each call date after `ref_date`, as (call date, call price, yield
+    // to call)
+    pub fn ytc(
+        &self,
+        ref_date: &NaiveDate,
+        clean_price: f64,
+        conv: Convention,
+    ) -> Vec<(NaiveDate, f64, Option<f64>)> {
+        let dirty_price = self.dirty_price(ref_date, clean_price);
+        self.calls
+            .range((Bound::Excluded(*ref_date), Bound::Unbounded))
+            .map(|(date, price)| {
+                let cashflow = self
+                    .call_cashflow(date, *price)
+                    .cf(ref_date, Some(dirty_price));
+                let ytc = self.cashflow_yield(ref_date, &cashflow, conv);
+                (*date, *price, ytc)
+            })
+            .collect()
+    }
+    // The yield to worst in `conv`, i.e., the lowest of the yield to maturity and the yields to
+    // call, together with the workout date that produces it. The calls whose yields can't be
+    // solved are skipped.
+    pub fn ytw(
+        &self,
+        ref_date: &NaiveDate,
+        clean_price: f64,
+        conv: Convention,
+    ) -> Option<(f64, NaiveDate)> {
+        let mut out = (self.result(ref_date, clean_price, conv)?.ytm, self.mty_date);
+        for (date, _, ytc) in self.ytc(ref_date, clean_price, conv) {
+            match ytc {
+                Some(ytc) if ytc < out.0 => out = (ytc, date),
+                _ => {}
+            }
+        }
+        Some(out)
+    }
+    // The embedded options after `ref_date`, as (years from `ref_date`, strike). Like the call
+    // cashflow, the strike is the exercise price of the outstanding principal plus the accrued.
+    fn option_strikes(
+        &self,
+        schedule: &BTreeMap<NaiveDate, f64>,
+        ref_date: &NaiveDate,
+    ) -> Vec<(f64, f64)> {
+        schedule
+            .range((Bound::Excluded(*ref_date), Bound::Unbounded))
+            .map(|(date, price)| {
+                let strike = self.outstanding(date) * price / 100.0 + self.cum_accrued(date);
+                (date_handle::year_frac(date, ref_date), strike)
+            })
+            .collect()
+    }
+    // The option-adjusted spread, i.e., the spread over `curve` as of `curve_date` that makes the
+    // short rate lattice value of the bond, with its call and put schedules, match the dirty
+    // price. The lattice is fitted to `curve` rolled forward to `ref_date`. The effective duration
+    // and convexity come from 1bp parallel shifts of the curve, keeping the OAS.
+    pub fn oas(
+        &self,
+        ref_date: &NaiveDate,
+        clean_price: f64,
+        curve: &ZeroCurve,
+        curve_date: &NaiveDate,
+        model: &HullWhite,
+    ) -> Option<BondOas> {
+        let dirty_price = self.dirty_price(ref_date, clean_price);
+        let cfs: Vec<(f64, f64)> = self
+            .cashflow(BondCfType::All)
+            .cf(ref_date, None)
+            .data
+            .iter()
+            .map(|(date, cf)| (date_handle::year_frac(date, ref_date), *cf))
+            .collect();
+        let horizon = cfs.last()?.0;
+        let curve = &curve.roll(date_handle::year_frac(ref_date, curve_date), horizon);
+        let calls = self.option_strikes(&self.calls, ref_date);
+        let puts = self.option_strikes(&self.puts, ref_date);
+        let value = |curve: &ZeroCurve, spread: f64| {
+            model
+                .tree(curve, horizon)
+                .value(&cfs, &calls, &puts, spread)
+        };
+        let tree = model.tree(curve, horizon);
+        let oas = find_root_in(0.0, -1.0, 1.0, |spread| {
+            tree.value(&cfs, &calls, &puts, spread) - dirty_price
+        })?;
+        let bp = 1e-4;
+        let mid = tree.value(&cfs, &calls, &puts, oas);
+        let up = value(&curve.shift(bp), oas);
+        let down = value(&curve.shift(-bp), oas);
+        Some(BondOas {
+            oas,
+            eff_dur: (down - up) / (2.0 * mid * bp),
+            eff_cvx: (up + down - 2.0 * mid) / (mid * bp * bp),
+        })
+    }
+    // The Z-spread, i.e., the spread over the continuously compounded zero rates of `curve` as of
+    // `curve_date`, that discounts the cashflows to the dirty price on `ref_date`
+    pub fn z_spread(
+        &self,
+        ref_date: &NaiveDate,
+        clean_price: f64,
+        curve: &ZeroCurve,
+        curve_date: &NaiveDate,
+    ) -> Option<f64> {
+        let dirty_price = self.dirty_price(ref_date, clean_price);
+        let cfs = self.curve_cashflows(ref_date, curve_date)?;
+        find_root_in(0.0, -1.0, 1.0, |spread| {
+            curve_value(&cfs, curve, |_| spread) - dirty_price
+        })
+    }
+    // The key rate durations at the key `tenors` (in years from `curve_date`), i.e., the price
+    // sensitivities to the 1bp triangular shocks of the zero rates of `curve` around each key
+    // tenor, keeping the Z-spread. They add up to the effective duration of the parallel shift.
+    pub fn key_rate_durations(
+        &self,
+        ref_date: &NaiveDate,
+        clean_price: f64,
+        curve: &ZeroCurve,
+        curve_date: &NaiveDate,
+        tenors: &[f64],
+    ) -> Option<Vec<f64>> {
+        let spread = self.z_spread(ref_date, clean_price, curve, curve_date)?;
+        let cfs = self.curve_cashflows(ref_date, curve_date)?;
+        let bp = 1e-4;
+        let mid = curve_value(&cfs, curve, |_| spread);
+        let krds = (0..tenors.len())
+            .map(|k| {
+                let shock = |t: f64| curve::key_rate_shock(tenors, k, t) * bp;
+                let up = curve_value(&cfs, curve, |t| spread + shock(t));
+                let down = curve_value(&cfs, curve, |t| spread - shock(t));
+                (down - up) / (2.0 * mid * bp)
+            })
+            .collect();
+        Some(krds)
+    }
+    // The cashflows after `ref_date` keyed by the years from `curve_date`, following the years
+    // of `ref_date` with a zero cashflow
+    fn curve_cashflows(
+        &self,
+        ref_date: &NaiveDate,
+        curve_date: &NaiveDate,
+    ) -> Option<Vec<(f64, f64)>> {
+        let cf = self.cashflow(BondCfType::All).cf(ref_date, None);
+        if cf.len() == 0 {
+            return None;
+        }
+        let mut cfs = vec![(date_handle::year_frac(ref_date, curve_date), 0.0)];
+        cfs.extend(
+            cf.data
+                .iter()
+                .map(|(date, cf)| (date_handle::year_frac(date, curve_date), *cf)),
+        );
+        Some(cfs)
+    }
+    // The index ratio of the inflation-linked bond on `ref_date`
+    pub fn index_ratio(&self, ref_date: &NaiveDate) -> Option<f64> {
+        let linker = self.linker.as_ref()?;
+        let base_cpi = linker.base_cpi.unwrap_or_else(|| {
+            linker
+                .cpi
+                .ref_cpi(&self.value_date, linker.lag, linker.interp)
+        });
+        Some(linker.cpi.ref_cpi(ref_date, linker.lag, linker.interp) / base_cpi)
+    }
+    // The inflation-adjusted cashflows, i.e., the (real) cashflows scaled by the index ratio
+    // on the payment dates, where the principal is floored at the par if required. It's the
+    // same as `cashflow()` if the bond is not linked to inflation.
+    pub fn inflation_cashflow(&self, cftype: BondCfType) -> Cashflow {
+        let mut res = self.cashflow(cftype);
+        let floor = self.linker.as_ref().map_or(false, |v| v.floor);
+        let redem = self.cashflow(BondCfType::Redem);
+        for (date, value) in res.data.iter_mut() {
+            let ratio = self.index_ratio(date).unwrap_or(1.0);
+            let redem_ratio = if floor { ratio.max(1.0) } else { ratio };
+            *value = match cftype {
+                BondCfType::Coupon => *value * ratio,
+                BondCfType::Redem => *value * redem_ratio,
+                BondCfType::All => {
+                    let redem = redem.data[date];
+                    (*value - redem) * ratio + redem * redem_ratio
+                }
+            };
+        }
+        for (date, (_, cpn)) in res.ex_cpns.iter_mut() {
+            *cpn *= self.index_ratio(date).unwrap_or(1.0);
+        }
+        res
+    }
+    // The real yield of the inflation-linked bond (i.e., the yield of the real cashflows given
+    // the real clean price) and the breakeven inflation against `nominal_ytm`, by Fisher's
+    // equation (1 + nominal) = (1 + real) * (1 + breakeven)
+    pub fn breakeven(
+        &self,
+        ref_date: &NaiveDate,
+        clean_price: f64,
+        nominal_ytm: f64,
+    ) -> Option<(f64, f64)> {
+        self.linker.as_ref()?;
+        let real_ytm = self.result(ref_date, clean_price, Convention::Annual)?.ytm;
+        Some((real_ytm, (1.0 + nominal_ytm) / (1.0 + real_ytm) - 1.0))
+    }
+    // The discount margin of the FRN, i.e., the margin over the index rates that discounts the
+    // projected cashflows to the dirty price. The index rate of each cashflow interval is fixed
+    // (or projected) in the same way as the coupons. The spread duration is the sensitivity of
+    // the price to the discount margin.
+    pub fn discount_margin(&self, ref_date: &NaiveDate, clean_price: f64) -> Option<BondDm> {
+        let float = self.float.as_ref()?;
+        let dirty_price = self.dirty_price(ref_date, clean_price);
+        let cashflow = self.cashflow(BondCfType::All).cf(ref_date, None);
+        let periods = self.cpn_periods();
+        let accrual_dates: BTreeMap<NaiveDate, NaiveDate> = self
+            .pay_schedule()
+            .into_iter()
+            .map(|(accrual_date, pay_date)| (pay_date, accrual_date))
+            .collect();
+        // the year fraction of the day count, summed over the coupon periods in between
+        let year_frac = |d0: &NaiveDate, d1: &NaiveDate| -> f64 {
+            periods
                 .iter()
-                .zip(&cfs)
-                .map(|(t, cf)| cf * t * (1.0 + ytm).powf(-t))
+                .filter(|p| p.end > *d0 && p.start < *d1)
+                .map(|p| {
+                    self.day_count
+                        .year_frac(d0.max(&p.start), d1.min(&p.end), p)
+                })
                 .sum()
-                / dirty_price;
-            macd
         };
-        Some(BondVal { ytm, macd, modd })
+        let mut start = *ref_date;
+        let mut intervals: Vec<(f64, f64, f64)> = Vec::new();
+        for (date, cf) in &cashflow.data {
+            let end = *accrual_dates.get(date).unwrap_or(date);
+            let fixing_date = self.calendar.add_bizdays(&start, -float.lookback);
+            let rate = float.index.rate(&fixing_date, &start, &end);
+            intervals.push((year_frac(&start, &end), rate, *cf));
+            start = end;
+        }
+        if intervals.is_empty() {
+            return None;
+        }
+        let npv = |dm: f64| {
+            let mut df = 1.0;
+            let mut value = 0.0;
+            for (t, rate, cf) in &intervals {
+                df /= 1.0 + (rate + dm) * t;
+                value += cf * df;
+            }
+            value
+        };
+        let dm = find_root_in(float.spread, -1.0, 1.0, |dm| npv(dm) - dirty_price)?;
+        let bp = 1e-4;
+        let spread_dur = (npv(dm - bp) - npv(dm + bp)) / (2.0 * npv(dm) * bp);
+        Some(BondDm { dm, spread_dur })
+    }
+    // The inverse of `result()`, which discounts the same cashflows (with a zero price at
+    // `ref_date`) by `ytm` in `conv`: annually compounded with `xnpv` for `Annual`, otherwise on
+    // the `yield_basis()`, i.e., compounded by coupon period for `Compound` and `Street` (the
+    // latter simple in the final period), continuously for `Continuous`, and by the CCDC rules
+    // for `ChinaInterbank`.
+    pub fn price(&self, ref_date: &NaiveDate, ytm: f64, conv: Convention) -> Option<BondPrice> {
+        let cashflow = self.cashflow(BondCfType::All).cf(ref_date, Some(0.0));
+        if cashflow.len() <= 1 {
+            return None;
+        }
+        let dates = cashflow.dates();
+        let cfs = cashflow.values();
+        let dirty_price = match conv {
+            Convention::Annual => xnpv(ytm, &cfs, &dates).ok()?,
+            _ => self.yield_basis(ref_date, &dates, conv).npv(ytm, &cfs),
+        };
+        let accrued = self.accrued(ref_date, true);
+        Some(BondPrice {
+            clean_price: (dirty_price - accrued) / self.factor(ref_date),
+            dirty_price,
+            accrued,
+        })
+    }
+    // The `cftype` cashflows after `ref_date` that belong to the holder on `horizon_date`, i.e.,
+    // the ones paid by then, plus the coupons traded ex by then but paid after
+    pub fn received(
+        &self,
+        cftype: BondCfType,
+        ref_date: &NaiveDate,
+        horizon_date: &NaiveDate,
+    ) -> Vec<(NaiveDate, f64)> {
+        let cashflow = self.cashflow(cftype);
+        let later = cashflow.cf(horizon_date, None).data;
+        cashflow
+            .cf(ref_date, None)
+            .data
+            .into_iter()
+            .map(|(date, value)| (date, value - later.get(&date).unwrap_or(&0.0)))
+            .filter(|(_, value)| *value != 0.0)
+            .collect()
+    }
+    // The total return from `ref_date` to `horizon_date`, where the cashflows received are
+    // reinvested at `reinvest_rate` (annually compounded) until the horizon, and the bond is
+    // sold at `exit_ytm` (the current yield if `None`) plus `spread_chg`, both in `conv`. The
+    // return is broken down into the coupon income (the coupons received plus the change of the
+    // accrued interest), the reinvestment income, the roll-down (the clean price change at the
+    // unchanged yield, plus the principal repaid) and the price change from the exit yield.
+    #[allow(clippy::too_many_arguments)]
+    pub fn horizon_return(
+        &self,
+        ref_date: &NaiveDate,
+        clean_price: f64,
+        horizon_date: &NaiveDate,
+        reinvest_rate: f64,
+        exit_ytm: Option<f64>,
+        spread_chg: f64,
+        conv: Convention,
+    ) -> Option<BondHorizon> {
+        if horizon_date <= ref_date {
+            return None;
+        }
+        let dirty_price = self.dirty_price(ref_date, clean_price);
+        let ytm = self.result(ref_date, clean_price, conv)?.ytm;
+        let exit_ytm = exit_ytm.unwrap_or(ytm) + spread_chg;
+        // nothing is left after the maturity
+        let horizon_value = |ytm: f64| self.price(horizon_date, ytm, conv);
+        let (carry_value, exit_value) = match horizon_value(ytm) {
+            Some(price) => (price.dirty_price, horizon_value(exit_ytm)?),
+            None => (
+                0.0,
+                BondPrice {
+                    clean_price: 0.0,
+                    dirty_price: 0.0,
+                    accrued: 0.0,
+                },
+            ),
+        };
+        let cpns = self.received(BondCfType::Coupon, ref_date, horizon_date);
+        let redems = self.received(BondCfType::Redem, ref_date, horizon_date);
+        let reinvest: f64 = cpns
+            .iter()
+            .chain(&redems)
+            .map(|(date, value)| {
+                let t = date_handle::year_frac(horizon_date, date);
+                value * ((1.0 + reinvest_rate).powf(t) - 1.0)
+            })
+            .sum();
+        let cpn: f64 = cpns.iter().map(|(_, value)| value).sum();
+        let redem: f64 = redems.iter().map(|(_, value)| value).sum();
+        let accrued = self.accrued(ref_date, true);
+        let income = cpn + exit_value.accrued - accrued;
+        let roll_down = carry_value - exit_value.accrued + redem - (dirty_price - accrued);
+        let price_chg = exit_value.dirty_price - carry_value;
+        Some(BondHorizon {
+            income: income / dirty_price,
+            reinvest: reinvest / dirty_price,
+            roll_down: roll_down / dirty_price,
+            price_chg: price_chg / dirty_price,
+            total: (income + reinvest + roll_down + price_chg) / dirty_price,
+            horizon_price: (carry_value != 0.0).then_some(exit_value.clean_price),
+        })
+    }
+    // The carry and roll-down from `ref_date` to `horizon_date`. The carry is the coupon income
+    // (the coupons received plus the change of the accrued interest) net of the cost of funding
+    // the dirty price at the simple `funding_rate` over ACT/365F. The roll-down is the clean
+    // price change (plus the principal repaid) when `curve` as of `curve_date` rolls forward
+    // unchanged to the horizon, i.e., the remaining cashflows are priced on the same zero rates
+    // by tenor plus the Z-spread on `ref_date`. The breakeven is the further parallel rise of the
+    // zero rates that wipes out both.
+    pub fn carry_roll(
+        &self,
+        ref_date: &NaiveDate,
+        clean_price: f64,
+        horizon_date: &NaiveDate,
+        funding_rate: f64,
+        curve: &ZeroCurve,
+        curve_date: &NaiveDate,
+    ) -> Option<BondCarry> {
+        if horizon_date <= ref_date {
+            return None;
+        }
+        let dirty_price = self.dirty_price(ref_date, clean_price);
+        let accrued = self.accrued(ref_date, true);
+        let horizon_accrued = self.accrued(horizon_date, true);
+        let cpn: f64 = self
+            .received(BondCfType::Coupon, ref_date, horizon_date)
+            .iter()
+            .map(|(_, value)| value)
+            .sum();
+        let redem: f64 = self
+            .received(BondCfType::Redem, ref_date, horizon_date)
+            .iter()
+            .map(|(_, value)| value)
+            .sum();
+        let days = (*horizon_date - *ref_date).num_days() as f64;
+        let carry = cpn + horizon_accrued - accrued - dirty_price * funding_rate * days / 365.0;
+        let spread = self.z_spread(ref_date, clean_price, curve, curve_date)?;
+        // the curve date moves with the horizon, so the zero rates by tenor are unchanged
+        let rolled_curve_date = *curve_date + (*horizon_date - *ref_date);
+        let cfs = self.curve_cashflows(horizon_date, &rolled_curve_date)?;
+        let price_chg = |spread: f64| {
+            curve_value(&cfs, curve, |_| spread) - horizon_accrued + redem - (dirty_price - accrued)
+        };
+        let roll_down = price_chg(spread);
+        let breakeven = find_root_in(0.0, -1.0, 1.0, |chg| carry + price_chg(spread + chg))?;
+        Some(BondCarry {
+            carry,
+            roll_down,
+            breakeven,
+        })
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::assert::NearEq;
     use chrono::NaiveDate;
 
     fn round(x: f64, digit: Option<u32>) -> f64 {
@@ -274,13 +1418,8 @@ mod tests {
         let ref_date = from_ymd(2010, 2, 1);
         assert_eq!(bond.accrued(&ref_date, true), 31.0 / 365.0 * 5.0);
 
-        let bond = FixedBond {
-            value_date: from_ymd(2010, 1, 1),
-            mty_date: from_ymd(2012, 1, 1),
-            redem_value: 100.0,
-            cpn_rate: 0.05,
-            cpn_freq: to_cpn_freq(0).unwrap(),
-        };
+        let bond =
+            FixedBond::new(from_ymd(2010, 1, 1), from_ymd(2012, 1, 1), 100.0, 0.05, 0).unwrap();
         let ref_date = from_ymd(2010, 2, 1);
         assert_eq!(
             bond.accrued(&ref_date, true),
@@ -289,45 +1428,53 @@ mod tests {
     }
     #[test]
     fn plain_bond() {
-        let bond = FixedBond {
-            value_date: from_ymd(2010, 1, 1),
-            mty_date: from_ymd(2020, 1, 1),
-            redem_value: 100.0,
-            cpn_rate: 0.05,
-            cpn_freq: to_cpn_freq(1).unwrap(),
-        };
+        let bond =
+            FixedBond::new(from_ymd(2010, 1, 1), from_ymd(2020, 1, 1), 100.0, 0.05, 1).unwrap();
         let ytm = 0.05;
         let ref_date = from_ymd(2010, 1, 1);
-        assert_eq!(rnd(bond.result(&ref_date, 100.0).unwrap().ytm), ytm);
+        assert_eq!(
+            rnd(bond
+                .result(&ref_date, 100.0, Convention::Annual)
+                .unwrap()
+                .ytm),
+            ytm
+        );
         // won't change as the price is clean
         let ref_date = from_ymd(2011, 1, 1);
-        assert_eq!(rnd(bond.result(&ref_date, 100.0).unwrap().ytm), ytm);
+        assert_eq!(
+            rnd(bond
+                .result(&ref_date, 100.0, Convention::Annual)
+                .unwrap()
+                .ytm),
+            ytm
+        );
         // won't change as the price is clean
         let ref_date = from_ymd(2011, 6, 15);
-        assert_eq!(rnd(bond.result(&ref_date, 100.0).unwrap().ytm), ytm);
+        assert_eq!(
+            rnd(bond
+                .result(&ref_date, 100.0, Convention::Annual)
+                .unwrap()
+                .ytm),
+            ytm
+        );
     }
     #[test]
     fn zero_cpn_bond() {
-        let bond = FixedBond {
-            value_date: from_ymd(2010, 1, 1),
-            mty_date: from_ymd(2011, 1, 1),
-            redem_value: 100.0,
-            cpn_rate: 0.05,
-            cpn_freq: to_cpn_freq(0).unwrap(),
-        };
+        let bond =
+            FixedBond::new(from_ymd(2010, 1, 1), from_ymd(2011, 1, 1), 100.0, 0.05, 0).unwrap();
         let ytm = 0.050000000000000114;
         let ref_date = from_ymd(2010, 1, 1);
-        assert_eq!(bond.result(&ref_date, 100.0).unwrap().ytm, ytm);
+        assert_eq!(
+            bond.result(&ref_date, 100.0, Convention::Annual)
+                .unwrap()
+                .ytm,
+            ytm
+        );
     }
     #[test]
     fn cashflow() {
-        let bond = FixedBond {
-            value_date: from_ymd(2010, 1, 1),
-            mty_date: from_ymd(2010, 8, 1),
-            redem_value: 100.0,
-            cpn_rate: 0.05,
-            cpn_freq: to_cpn_freq(2).unwrap(),
-        };
+        let bond =
+            FixedBond::new(from_ymd(2010, 1, 1), from_ymd(2010, 8, 1), 100.0, 0.05, 2).unwrap();
         let out = bond.cashflow(BondCfType::All).data;
         let mut expect: BTreeMap<NaiveDate, f64> = BTreeMap::new();
         expect.insert(from_ymd(2010, 7, 1), 2.5);
@@ -335,54 +1482,993 @@ mod tests {
         assert_eq!(out, expect);
     }
     #[test]
-    fn dur() {
-        let bond = FixedBond {
-            value_date: from_ymd(2010, 1, 1),
-            mty_date: from_ymd(2015, 1, 1),
-            redem_value: 100.0,
-            cpn_rate: 0.05,
-            cpn_freq: to_cpn_freq(0).unwrap(),
+    fn day_count() {
+        let bond = FixedBond::new(from_ymd(2010, 1, 1), from_ymd(2012, 1, 1), 100.0, 0.05, 2)
+            .unwrap()
+            .with_day_count(DayCount::Act360);
+        let ref_date = from_ymd(2010, 2, 1);
+        assert_near_eq!(bond.accrued(&ref_date, true), 5.0 * 31.0 / 360.0);
+        let cf = bond.cashflow(BondCfType::Coupon);
+        assert_near_eq!(cf.values()[0], 5.0 * 181.0 / 360.0);
+        assert_near_eq!(cf.values()[1], 5.0 * 184.0 / 360.0);
+
+        let bond = bond.with_day_count(DayCount::Thirty360Us);
+        assert_near_eq!(bond.accrued(&ref_date, true), 5.0 * 30.0 / 360.0);
+        let cf = bond.cashflow(BondCfType::Coupon);
+        assert_near_eq!(cf.values(), vec![2.5; 4]);
+
+        // zero coupon bond accrues over the whole life
+        let bond = FixedBond::new(from_ymd(2010, 1, 1), from_ymd(2011, 7, 1), 100.0, 0.05, 0)
+            .unwrap()
+            .with_day_count(DayCount::Act365F);
+        let cf = bond.cashflow(BondCfType::Coupon);
+        assert_near_eq!(cf.values(), vec![5.0 * 546.0 / 365.0]);
+        let bond = bond.with_day_count(DayCount::ActActIcma);
+        let cf = bond.cashflow(BondCfType::Coupon);
+        assert_near_eq!(cf.values(), vec![5.0 * (181.0 / 365.0 + 1.0)]);
+    }
+    #[test]
+    fn odd_first_cpn() {
+        let bond =
+            FixedBond::new(from_ymd(2020, 3, 15), from_ymd(2022, 1, 15), 100.0, 0.05, 2).unwrap();
+        // rolls forward by default
+        let cf = bond.cashflow(BondCfType::Coupon);
+        assert_eq!(cf.dates()[0], from_ymd(2020, 9, 15));
+        assert_eq!(*cf.dates().last().unwrap(), from_ymd(2022, 1, 15));
+
+        let bond = bond.with_stub(Stub::ShortFirst);
+        let cf = bond.cashflow(BondCfType::Coupon);
+        assert_eq!(
+            cf.dates(),
+            vec![
+                from_ymd(2020, 7, 15),
+                from_ymd(2021, 1, 15),
+                from_ymd(2021, 7, 15),
+                from_ymd(2022, 1, 15)
+            ]
+        );
+        assert_near_eq!(cf.values()[0], 2.5 * 122.0 / 182.0);
+        assert_near_eq!(cf.values()[1], 2.5);
+        let ref_date = from_ymd(2020, 5, 15);
+        assert_near_eq!(bond.accrued(&ref_date, true), 2.5 * 61.0 / 182.0);
+
+        let bond = bond.with_stub(Stub::LongFirst);
+        let cf = bond.cashflow(BondCfType::Coupon);
+        assert_eq!(cf.dates()[0], from_ymd(2021, 1, 15));
+        assert_near_eq!(cf.values()[0], 2.5 * 122.0 / 182.0 + 2.5);
+        let ref_date = from_ymd(2020, 9, 15);
+        assert_near_eq!(
+            bond.accrued(&ref_date, true),
+            2.5 * 122.0 / 182.0 + 2.5 * 62.0 / 184.0
+        );
+
+        // the explicit first coupon date
+        let bond = bond
+            .with_first_cpn_date(Some(from_ymd(2020, 7, 15)))
+            .unwrap();
+        let cf = bond.cashflow(BondCfType::Coupon);
+        assert_eq!(cf.dates()[0], from_ymd(2020, 7, 15));
+        assert_near_eq!(cf.values()[0], 2.5 * 122.0 / 182.0);
+        let bond = bond.with_first_cpn_date(Some(from_ymd(2020, 3, 1)));
+        assert!(bond.is_err());
+    }
+    #[test]
+    fn odd_last_cpn() {
+        let bond =
+            FixedBond::new(from_ymd(2020, 1, 15), from_ymd(2021, 9, 15), 100.0, 0.05, 2).unwrap();
+        // the default short last period
+        let cf = bond.cashflow(BondCfType::Coupon);
+        assert_eq!(*cf.dates().last().unwrap(), from_ymd(2021, 9, 15));
+        assert_near_eq!(*cf.values().last().unwrap(), 2.5 * 62.0 / 184.0);
+
+        let bond = bond.with_stub(Stub::LongLast);
+        let cf = bond.cashflow(BondCfType::Coupon);
+        assert_eq!(
+            cf.dates(),
+            vec![
+                from_ymd(2020, 7, 15),
+                from_ymd(2021, 1, 15),
+                from_ymd(2021, 9, 15)
+            ]
+        );
+        assert_near_eq!(*cf.values().last().unwrap(), 2.5 + 2.5 * 62.0 / 184.0);
+        let ref_date = from_ymd(2021, 8, 15);
+        assert_near_eq!(bond.accrued(&ref_date, true), 2.5 + 2.5 * 31.0 / 184.0);
+
+        // the explicit penultimate coupon date with the regular periods anchored on it
+        let bond = FixedBond::new(from_ymd(2020, 3, 1), from_ymd(2021, 9, 15), 100.0, 0.05, 2)
+            .unwrap()
+            .with_penultimate_cpn_date(Some(from_ymd(2021, 3, 1)))
+            .unwrap();
+        let cf = bond.cashflow(BondCfType::Coupon);
+        assert_eq!(
+            cf.dates(),
+            vec![
+                from_ymd(2020, 9, 1),
+                from_ymd(2021, 3, 1),
+                from_ymd(2021, 9, 15)
+            ]
+        );
+        assert_near_eq!(cf.values()[1], 2.5);
+        assert_near_eq!(cf.values()[2], 2.5 + 2.5 * 14.0 / 181.0);
+        let res = bond
+            .result(&from_ymd(2021, 4, 1), 100.0, Convention::Annual)
+            .unwrap();
+        assert!(res.ytm > 0.0);
+        let price = bond
+            .price(&from_ymd(2021, 4, 1), res.ytm, Convention::Annual)
+            .unwrap();
+        assert_near_eq!(rnd(price.clean_price), 100.0);
+
+        let bond = FixedBond::new(from_ymd(2020, 3, 1), from_ymd(2021, 9, 15), 100.0, 0.05, 2)
+            .unwrap()
+            .with_first_cpn_date(Some(from_ymd(2021, 3, 1)))
+            .unwrap()
+            .with_penultimate_cpn_date(Some(from_ymd(2020, 9, 1)));
+        assert!(bond.is_err());
+    }
+    #[test]
+    fn bizday_adjusted() {
+        // 2022-04-30 and 2022-10-30 are weekends and 2022-05-02 is a holiday
+        let calendar = Rc::new(Calendar::new(vec![from_ymd(2022, 5, 2)]));
+        let bond = FixedBond::new(
+            from_ymd(2021, 10, 30),
+            from_ymd(2022, 10, 30),
+            100.0,
+            0.05,
+            2,
+        )
+        .unwrap()
+        .with_day_count(DayCount::Act365F);
+        let unadjusted = bond.cashflow(BondCfType::All);
+        let bond = bond.with_calendar(calendar.clone(), BusDayConv::Following, false);
+        let cf = bond.cashflow(BondCfType::All);
+        assert_eq!(
+            cf.dates(),
+            vec![from_ymd(2022, 5, 3), from_ymd(2022, 10, 31)]
+        );
+        // the accrual periods stay unadjusted
+        assert_eq!(cf.values(), unadjusted.values());
+        assert_eq!(
+            bond.pay_schedule(),
+            vec![
+                (from_ymd(2022, 4, 30), from_ymd(2022, 5, 3)),
+                (from_ymd(2022, 10, 30), from_ymd(2022, 10, 31))
+            ]
+        );
+        // the coupon is still paid to the holder on 2022-05-01 while the new period accrues
+        let ref_date = from_ymd(2022, 5, 1);
+        assert_near_eq!(bond.accrued(&ref_date, true), 5.0 / 365.0);
+        assert_eq!(bond.cashflow(BondCfType::All).cf(&ref_date, None).len(), 2);
+
+        let bond = bond.with_calendar(calendar.clone(), BusDayConv::ModifiedFollowing, true);
+        let cf = bond.cashflow(BondCfType::Coupon);
+        assert_eq!(
+            cf.dates(),
+            vec![from_ymd(2022, 4, 29), from_ymd(2022, 10, 31)]
+        );
+        assert_near_eq!(cf.values()[0], 5.0 * 181.0 / 365.0);
+        assert_near_eq!(cf.values()[1], 5.0 * 185.0 / 365.0);
+
+        let bond = bond.with_calendar(calendar, BusDayConv::Preceding, false);
+        let cf = bond.cashflow(BondCfType::Coupon);
+        assert_eq!(
+            cf.dates(),
+            vec![from_ymd(2022, 4, 29), from_ymd(2022, 10, 28)]
+        );
+    }
+    #[test]
+    fn eom_rule() {
+        let bond = FixedBond::new(
+            from_ymd(2021, 4, 30),
+            from_ymd(2022, 10, 31),
+            100.0,
+            0.05,
+            2,
+        )
+        .unwrap();
+        let cf = bond.cashflow(BondCfType::Coupon);
+        assert_eq!(cf.dates()[0], from_ymd(2021, 10, 30));
+        let bond = bond.with_eom(true);
+        let cf = bond.cashflow(BondCfType::Coupon);
+        assert_eq!(
+            cf.dates(),
+            vec![
+                from_ymd(2021, 10, 31),
+                from_ymd(2022, 4, 30),
+                from_ymd(2022, 10, 31)
+            ]
+        );
+        assert_near_eq!(cf.values(), vec![2.5; 3]);
+        let ref_date = from_ymd(2021, 11, 30);
+        assert_near_eq!(bond.accrued(&ref_date, true), 2.5 * 30.0 / 181.0);
+
+        // backward from a month end maturity
+        let bond = FixedBond::new(from_ymd(2021, 1, 15), from_ymd(2022, 2, 28), 100.0, 0.05, 2)
+            .unwrap()
+            .with_stub(Stub::ShortFirst)
+            .with_eom(true);
+        let cf = bond.cashflow(BondCfType::Coupon);
+        assert_eq!(
+            cf.dates(),
+            vec![
+                from_ymd(2021, 2, 28),
+                from_ymd(2021, 8, 31),
+                from_ymd(2022, 2, 28)
+            ]
+        );
+        assert_near_eq!(cf.values()[0], 2.5 * 44.0 / 181.0);
+    }
+    #[test]
+    fn amortizing() {
+        let mut amort: BTreeMap<NaiveDate, f64> = BTreeMap::new();
+        amort.insert(from_ymd(2011, 1, 1), 40.0);
+        amort.insert(from_ymd(2011, 7, 1), 20.0);
+        let bond = FixedBond::new(from_ymd(2010, 1, 1), from_ymd(2012, 1, 1), 100.0, 0.05, 1)
+            .unwrap()
+            .with_amortization(amort)
+            .unwrap();
+        let cpn = bond.cashflow(BondCfType::Coupon);
+        let redem = bond.cashflow(BondCfType::Redem);
+        assert_eq!(cpn.dates(), redem.dates());
+        assert_eq!(
+            cpn.dates(),
+            vec![
+                from_ymd(2011, 1, 1),
+                from_ymd(2011, 7, 1),
+                from_ymd(2012, 1, 1)
+            ]
+        );
+        assert_near_eq!(redem.values(), vec![40.0, 20.0, 40.0]);
+        // the coupon accrues on 60 before 2011-07-01 and 40 after
+        assert_near_eq!(
+            cpn.values(),
+            vec![
+                5.0,
+                0.0,
+                0.05 * (60.0 * 181.0 / 365.0 + 40.0 * 184.0 / 365.0)
+            ]
+        );
+        let ref_date = from_ymd(2011, 7, 1);
+        assert_near_eq!(bond.factor(&ref_date), 0.4);
+        let res = bond.result(&ref_date, 100.0, Convention::Annual).unwrap();
+        let price = bond.price(&ref_date, res.ytm, Convention::Annual).unwrap();
+        assert_near_eq!(rnd(price.clean_price), 100.0);
+        assert_near_eq!(rnd(price.dirty_price), rnd(40.0 + price.accrued));
+
+        let mut amort: BTreeMap<NaiveDate, f64> = BTreeMap::new();
+        amort.insert(from_ymd(2011, 1, 1), 140.0);
+        let bond = FixedBond::new(from_ymd(2010, 1, 1), from_ymd(2012, 1, 1), 100.0, 0.05, 1)
+            .unwrap()
+            .with_amortization(amort);
+        assert!(bond.is_err());
+    }
+    #[test]
+    fn callable() {
+        let mut calls: BTreeMap<NaiveDate, f64> = BTreeMap::new();
+        calls.insert(from_ymd(2023, 1, 1), 100.0);
+        calls.insert(from_ymd(2024, 1, 1), 101.0);
+        let bond = FixedBond::new(from_ymd(2020, 1, 1), from_ymd(2025, 1, 1), 100.0, 0.05, 1)
+            .unwrap()
+            .with_calls(calls)
+            .unwrap();
+        let ref_date = from_ymd(2021, 1, 1);
+        let ytc = bond.ytc(&ref_date, 105.0, Convention::Annual);
+        assert_eq!(ytc.len(), 2);
+        let cfs = [-105.0, 5.0, 105.0];
+        let dates = [ref_date, from_ymd(2022, 1, 1), from_ymd(2023, 1, 1)];
+        assert!(xnpv(ytc[0].2.unwrap(), &cfs, &dates).unwrap().abs() < 1e-8);
+        let cfs = [-105.0, 5.0, 5.0, 106.0];
+        let dates = [
+            ref_date,
+            from_ymd(2022, 1, 1),
+            from_ymd(2023, 1, 1),
+            from_ymd(2024, 1, 1),
+        ];
+        assert!(xnpv(ytc[1].2.unwrap(), &cfs, &dates).unwrap().abs() < 1e-8);
+        // the premium bond works out at the first call
+        let (ytw, workout_date) = bond.ytw(&ref_date, 105.0, Convention::Annual).unwrap();
+        assert_eq!(workout_date, from_ymd(2023, 1, 1));
+        assert_eq!(ytw, ytc[0].2.unwrap());
+        // the yields follow the convention of the yield to maturity
+        let ytc = bond.ytc(&ref_date, 105.0, Convention::Continuous);
+        assert_near_eq!(ytc[0].2.unwrap(), (1.0 + ytw).ln());
+        let (ytw, _) = bond.ytw(&ref_date, 105.0, Convention::Continuous).unwrap();
+        assert_eq!(ytw, ytc[0].2.unwrap());
+        // the discount bond works out at maturity
+        let (ytw, workout_date) = bond.ytw(&ref_date, 95.0, Convention::Annual).unwrap();
+        assert_eq!(workout_date, from_ymd(2025, 1, 1));
+        assert_eq!(
+            ytw,
+            bond.result(&ref_date, 95.0, Convention::Annual)
+                .unwrap()
+                .ytm
+        );
+        // the accrued coupon is paid when called between the coupon dates
+        let call_date = from_ymd(2023, 7, 1);
+        let cf = bond.call_cashflow(&call_date, 100.0);
+        assert_eq!(cf.dates().last(), Some(&call_date));
+        assert_near_eq!(cf.values()[3], 100.0 + 5.0 * 181.0 / 365.0);
+        assert!(bond
+            .ytc(&from_ymd(2024, 1, 1), 100.0, Convention::Annual)
+            .is_empty());
+        // the yield to the call on the next day can't be solved, but the others can
+        let ref_date = from_ymd(2022, 12, 31);
+        let ytc = bond.ytc(&ref_date, 50.0, Convention::Annual);
+        assert!(ytc[0].2.is_none());
+        let (ytw, workout_date) = bond.ytw(&ref_date, 50.0, Convention::Annual).unwrap();
+        assert_eq!(workout_date, from_ymd(2025, 1, 1));
+        assert_eq!(
+            ytw,
+            bond.result(&ref_date, 50.0, Convention::Annual)
+                .unwrap()
+                .ytm
+        );
+        // called on Saturday 2022-01-01, the coupon ending on the call date is paid in full
+        // with the call price on the following Monday
+        let mut calls: BTreeMap<NaiveDate, f64> = BTreeMap::new();
+        calls.insert(from_ymd(2022, 1, 1), 100.0);
+        let bond = FixedBond::new(from_ymd(2020, 1, 1), from_ymd(2025, 1, 1), 100.0, 0.05, 1)
+            .unwrap()
+            .with_calendar(Rc::new(Calendar::new(vec![])), BusDayConv::Following, false)
+            .with_calls(calls)
+            .unwrap();
+        let cf = bond.call_cashflow(&from_ymd(2022, 1, 1), 100.0);
+        assert_eq!(cf.dates(), vec![from_ymd(2021, 1, 1), from_ymd(2022, 1, 3)]);
+        assert_eq!(cf.values(), vec![5.0, 105.0]);
+        let ref_date = from_ymd(2021, 6, 1);
+        let ytc = bond.ytc(&ref_date, 100.0, Convention::Annual)[0].2.unwrap();
+        let ytm = bond
+            .result(&ref_date, 100.0, Convention::Annual)
+            .unwrap()
+            .ytm;
+        assert!((ytc - ytm).abs() < 1e-3);
+        // called within the ex-coupon period, the issuer still pays the cum-coupon accrued
+        let call_date = from_ymd(2021, 12, 28);
+        let mut calls: BTreeMap<NaiveDate, f64> = BTreeMap::new();
+        calls.insert(call_date, 100.0);
+        let bond = FixedBond::new(from_ymd(2020, 1, 1), from_ymd(2025, 1, 1), 100.0, 0.05, 1)
+            .unwrap()
+            .with_ex_cpn(10, false)
+            .unwrap()
+            .with_calls(calls.clone())
+            .unwrap()
+            .with_puts(calls.clone())
+            .unwrap();
+        assert!(bond.accrued(&call_date, true) < 0.0);
+        let cum = 100.0 + 5.0 * 361.0 / 365.0;
+        let cf = bond.call_cashflow(&call_date, 100.0);
+        assert_eq!(cf.dates().last(), Some(&call_date));
+        assert_near_eq!(*cf.values().last().unwrap(), cum);
+        let ytc = bond.ytc(&ref_date, 100.0, Convention::Annual)[0].2.unwrap();
+        let ytm = bond
+            .result(&ref_date, 100.0, Convention::Annual)
+            .unwrap()
+            .ytm;
+        assert!((ytc - ytm).abs() < 1e-3);
+        let strikes = bond.option_strikes(&calls, &ref_date);
+        assert_near_eq!(strikes[0].1, cum);
+
+        let mut calls: BTreeMap<NaiveDate, f64> = BTreeMap::new();
+        calls.insert(from_ymd(2025, 1, 1), 100.0);
+        let bond = FixedBond::new(from_ymd(2020, 1, 1), from_ymd(2025, 1, 1), 100.0, 0.05, 1)
+            .unwrap()
+            .with_calls(calls);
+        assert!(bond.is_err());
+    }
+    #[test]
+    fn oas() {
+        let new_bond =
+            || FixedBond::new(from_ymd(2020, 1, 1), from_ymd(2025, 1, 1), 100.0, 0.05, 1).unwrap();
+        let bond = new_bond();
+        let ref_date = from_ymd(2021, 1, 1);
+        let res = bond.result(&ref_date, 105.0, Convention::Annual).unwrap();
+        // no spread over the flat curve of the (continuously compounded) yield
+        let curve = ZeroCurve::new(vec![1.0], vec![(1.0 + res.ytm).ln()]).unwrap();
+        let model = HullWhite::new(0.03, 0.01).unwrap();
+        let straight = bond
+            .oas(&ref_date, 105.0, &curve, &ref_date, &model)
+            .unwrap();
+        assert!(straight.oas.abs() < 1e-8);
+        assert!((straight.eff_dur - res.macd).abs() < 1e-4);
+        assert!(straight.eff_cvx > 0.0);
+        // the flat curve as of an earlier date rolls to itself
+        let rolled = bond
+            .oas(&ref_date, 105.0, &curve, &from_ymd(2020, 7, 1), &model)
+            .unwrap();
+        assert!(rolled.oas.abs() < 1e-8);
+        assert!((rolled.eff_dur - straight.eff_dur).abs() < 1e-8);
+
+        let mut calls: BTreeMap<NaiveDate, f64> = BTreeMap::new();
+        calls.insert(from_ymd(2023, 1, 1), 100.0);
+        let mut puts: BTreeMap<NaiveDate, f64> = BTreeMap::new();
+        puts.insert(from_ymd(2023, 1, 1), 100.0);
+        let callable = new_bond().with_calls(calls).unwrap();
+        let callable = callable
+            .oas(&ref_date, 105.0, &curve, &ref_date, &model)
+            .unwrap();
+        assert!(callable.oas < straight.oas);
+        assert!(callable.eff_dur < straight.eff_dur);
+        let putable = new_bond().with_puts(puts).unwrap();
+        let putable = putable
+            .oas(&ref_date, 105.0, &curve, &ref_date, &model)
+            .unwrap();
+        assert!(putable.oas > straight.oas);
+        assert!(putable.eff_dur < straight.eff_dur);
+    }
+    #[test]
+    fn z_spread() {
+        let bond =
+            FixedBond::new(from_ymd(2020, 1, 1), from_ymd(2025, 1, 1), 100.0, 0.05, 1).unwrap();
+        let ref_date = from_ymd(2021, 1, 1);
+        let ytm = bond
+            .result(&ref_date, 105.0, Convention::Annual)
+            .unwrap()
+            .ytm;
+        // the spread of the continuously compounded yield over the flat curve
+        let curve = ZeroCurve::new(vec![1.0], vec![0.01]).unwrap();
+        let spread = bond.z_spread(&ref_date, 105.0, &curve, &ref_date).unwrap();
+        assert!((spread - ((1.0 + ytm).ln() - 0.01)).abs() < 1e-8);
+        // the curve as of an earlier date is rolled forward to `ref_date`
+        let curve = ZeroCurve::new(vec![1.0, 5.0], vec![0.01, 0.01]).unwrap();
+        let curve_date = from_ymd(2020, 7, 1);
+        let rolled = bond
+            .z_spread(&ref_date, 105.0, &curve, &curve_date)
+            .unwrap();
+        assert!((rolled - spread).abs() < 1e-8);
+        let upward = ZeroCurve::new(vec![1.0, 5.0], vec![0.01, 0.03]).unwrap();
+        let upward = bond.z_spread(&ref_date, 105.0, &upward, &ref_date).unwrap();
+        assert!(upward < spread);
+        assert!(bond
+            .z_spread(&from_ymd(2025, 1, 1), 100.0, &curve, &ref_date)
+            .is_none());
+    }
+    #[test]
+    fn key_rate_durations() {
+        let bond =
+            FixedBond::new(from_ymd(2020, 1, 1), from_ymd(2030, 1, 1), 100.0, 0.05, 2).unwrap();
+        let ref_date = from_ymd(2021, 3, 1);
+        let curve = ZeroCurve::new(vec![1.0, 5.0, 10.0], vec![0.02, 0.03, 0.035]).unwrap();
+        let tenors = [1.0, 2.0, 3.0, 5.0, 7.0, 10.0, 20.0, 30.0];
+        let krds = bond
+            .key_rate_durations(&ref_date, 105.0, &curve, &ref_date, &tenors)
+            .unwrap();
+        assert_eq!(krds.len(), 8);
+        // the cashflows end before 10Y
+        assert_eq!(&krds[6..], &[0.0, 0.0]);
+        assert!(krds[5] > krds[0]);
+        // they add up to the effective duration of the parallel shift, keeping the Z-spread
+        let spread = bond.z_spread(&ref_date, 105.0, &curve, &ref_date).unwrap();
+        let dirty_price = bond.dirty_price(&ref_date, 105.0);
+        let bp = 1e-4;
+        let price = |shift: f64| {
+            let cfs = bond.curve_cashflows(&ref_date, &ref_date).unwrap();
+            curve_value(&cfs, &curve, |_| spread + shift)
+        };
+        assert!((price(0.0) - dirty_price).abs() < 1e-6);
+        let eff_dur = (price(-bp) - price(bp)) / (2.0 * price(0.0) * bp);
+        assert!((krds.iter().sum::<f64>() - eff_dur).abs() < 1e-6);
+    }
+    #[test]
+    fn frn() {
+        use crate::float_rate::RateIndex;
+        let mut fixings: BTreeMap<NaiveDate, f64> = BTreeMap::new();
+        fixings.insert(from_ymd(2020, 12, 31), 0.02);
+        fixings.insert(from_ymd(2021, 3, 31), 0.03);
+        let index = Rc::new(RateIndex::new(fixings, None).unwrap());
+        let new_frn = |reset_months: Option<i32>, lookback: i64, day_count: DayCount| {
+            FixedBond::new(from_ymd(2021, 1, 1), from_ymd(2022, 1, 1), 100.0, 0.0, 2)
+                .unwrap()
+                .with_day_count(day_count)
+                .with_float(Some(FloatCpn {
+                    index: index.clone(),
+                    spread: 0.005,
+                    reset_months,
+                    lookback,
+                }))
+                .unwrap()
+        };
+        // fixed 1 business day before the reset, 2021-01-01 and 2021-04-01 are not fixing dates
+        let bond = new_frn(Some(3), 1, DayCount::Act360);
+        let cpns = bond.cashflow(BondCfType::Coupon).values();
+        assert_near_eq!(cpns[0], 100.0 * (0.025 * 90.0 + 0.035 * 91.0) / 360.0);
+        // projected with the last fixing
+        assert_near_eq!(cpns[1], 100.0 * 0.035 * 184.0 / 360.0);
+        let ref_date = from_ymd(2021, 5, 1);
+        assert_near_eq!(
+            bond.accrued(&ref_date, true),
+            100.0 * (0.025 * 90.0 + 0.035 * 30.0) / 360.0
+        );
+        // reset once per coupon period
+        let bond = new_frn(None, 0, DayCount::Act360);
+        let cpns = bond.cashflow(BondCfType::Coupon).values();
+        assert_near_eq!(cpns[0], 100.0 * 0.025 * 181.0 / 360.0);
+
+        // the discount margin accrues on the same basis as the coupons
+        let bond = new_frn(Some(3), 1, DayCount::Thirty360Us);
+        let ref_date = from_ymd(2021, 7, 1);
+        let dm = bond.discount_margin(&ref_date, 100.0).unwrap();
+        // at par, the discount margin equals the spread
+        assert_near_eq!(round(dm.dm, Some(8)), 0.005);
+        assert!((dm.spread_dur - 0.5).abs() < 0.01);
+        let dm = bond.discount_margin(&ref_date, 99.0).unwrap();
+        assert!(dm.dm > 0.005);
+        let bond = new_frn(Some(3), 1, DayCount::Act360);
+        let dm = bond.discount_margin(&ref_date, 100.0).unwrap();
+        assert_near_eq!(round(dm.dm, Some(8)), 0.005);
+        let bond = FixedBond::new(from_ymd(2021, 1, 1), from_ymd(2022, 1, 1), 100.0, 0.0, 2)
+            .unwrap()
+            .with_float(Some(FloatCpn {
+                index,
+                spread: 0.005,
+                reset_months: Some(0),
+                lookback: 0,
+            }));
+        assert!(bond.is_err());
+    }
+    #[test]
+    fn step_cpn() {
+        let mut cpn_schedule: BTreeMap<NaiveDate, f64> = BTreeMap::new();
+        cpn_schedule.insert(from_ymd(2022, 1, 1), 0.06);
+        // steps within a coupon period
+        cpn_schedule.insert(from_ymd(2022, 10, 1), 0.04);
+        let bond = FixedBond::new(from_ymd(2020, 1, 1), from_ymd(2024, 1, 1), 100.0, 0.05, 1)
+            .unwrap()
+            .with_day_count(DayCount::Act365F)
+            .with_cpn_schedule(cpn_schedule)
+            .unwrap();
+        assert_near_eq!(
+            bond.cashflow(BondCfType::Coupon).values(),
+            vec![
+                5.0 * 366.0 / 365.0,
+                5.0,
+                6.0 * 273.0 / 365.0 + 4.0 * 92.0 / 365.0,
+                4.0
+            ]
+        );
+        assert_near_eq!(
+            bond.accrued(&from_ymd(2022, 11, 1), true),
+            6.0 * 273.0 / 365.0 + 4.0 * 31.0 / 365.0
+        );
+        let res = bond
+            .result(&from_ymd(2022, 11, 1), 100.0, Convention::Annual)
+            .unwrap();
+        let price = bond
+            .price(&from_ymd(2022, 11, 1), res.ytm, Convention::Annual)
+            .unwrap();
+        assert_near_eq!(round(price.clean_price, Some(8)), 100.0);
+
+        let mut cpn_schedule: BTreeMap<NaiveDate, f64> = BTreeMap::new();
+        cpn_schedule.insert(from_ymd(2024, 1, 1), 0.06);
+        let bond = FixedBond::new(from_ymd(2020, 1, 1), from_ymd(2024, 1, 1), 100.0, 0.05, 1)
+            .unwrap()
+            .with_cpn_schedule(cpn_schedule);
+        assert!(bond.is_err());
+    }
+    #[test]
+    fn inflation_linked() {
+        use crate::inflation::CpiIndex;
+        let mut cpi: BTreeMap<NaiveDate, f64> = BTreeMap::new();
+        cpi.insert(from_ymd(2020, 10, 1), 100.0);
+        cpi.insert(from_ymd(2020, 11, 1), 101.0);
+        cpi.insert(from_ymd(2021, 4, 1), 98.0);
+        let cpi = Rc::new(CpiIndex::new(cpi).unwrap());
+        let new_linker = |base_cpi: Option<f64>, floor: bool| {
+            FixedBond::new(from_ymd(2021, 1, 1), from_ymd(2022, 1, 1), 100.0, 0.02, 2)
+                .unwrap()
+                .with_linker(Some(Linker {
+                    cpi: cpi.clone(),
+                    base_cpi,
+                    lag: 3,
+                    interp: true,
+                    floor,
+                }))
+                .unwrap()
         };
+        let bond = new_linker(None, false);
+        assert_near_eq!(bond.index_ratio(&from_ymd(2021, 1, 1)).unwrap(), 1.0);
+        assert_near_eq!(
+            bond.index_ratio(&from_ymd(2021, 1, 16)).unwrap(),
+            1.0 + 15.0 / 31.0 * 0.01
+        );
+        // 2021-07-01 refers to Apr and 2022-01-01 to Oct, which is projected flat
+        let cf = bond.inflation_cashflow(BondCfType::All);
+        assert_near_eq!(cf.values(), vec![0.98, 98.0 + 0.98]);
+        let bond = new_linker(None, true);
+        let cf = bond.inflation_cashflow(BondCfType::All);
+        assert_near_eq!(cf.values(), vec![0.98, 100.0 + 0.98]);
+        let bond = new_linker(Some(98.0), true);
+        let cf = bond.inflation_cashflow(BondCfType::Coupon);
+        assert_near_eq!(cf.values(), vec![1.0, 1.0]);
+        let ref_date = from_ymd(2021, 1, 1);
+        let (real_ytm, breakeven) = bond.breakeven(&ref_date, 100.0, 0.03).unwrap();
+        assert_eq!(
+            real_ytm,
+            bond.result(&ref_date, 100.0, Convention::Annual)
+                .unwrap()
+                .ytm
+        );
+        assert_near_eq!((1.0 + real_ytm) * (1.0 + breakeven), 1.03);
+
+        let bond =
+            FixedBond::new(from_ymd(2021, 1, 1), from_ymd(2022, 1, 1), 100.0, 0.02, 2).unwrap();
+        assert!(bond.index_ratio(&ref_date).is_none());
+        assert!(bond.breakeven(&ref_date, 100.0, 0.03).is_none());
+        assert_eq!(
+            bond.inflation_cashflow(BondCfType::All).values(),
+            bond.cashflow(BondCfType::All).values()
+        );
+    }
+    #[test]
+    fn yield_conventions() {
+        let bond =
+            FixedBond::new(from_ymd(2020, 1, 1), from_ymd(2025, 1, 1), 100.0, 0.05, 2).unwrap();
+        let ref_date = from_ymd(2021, 1, 1);
+        let res = bond.result(&ref_date, 100.0, Convention::Annual).unwrap();
+        assert_near_eq!(round(res.ytm, Some(8)), 1.025f64.powi(2) - 1.0);
+        let res = bond.result(&ref_date, 100.0, Convention::Compound).unwrap();
+        assert_near_eq!(round(res.ytm, Some(8)), 0.05);
+        assert_near_eq!(round(res.modd, Some(6)), round(res.macd / 1.025, Some(6)));
+        let res2 = bond.result(&ref_date, 100.0, Convention::Street).unwrap();
+        assert_near_eq!(res2.ytm, res.ytm);
+        let res = bond
+            .result(&ref_date, 100.0, Convention::Continuous)
+            .unwrap();
+        assert_near_eq!(round(res.ytm, Some(8)), round(2.0 * 1.025f64.ln(), Some(8)));
+        assert_near_eq!(round(res.modd, Some(6)), round(res.macd, Some(6)));
+        // the simple yield in the final period, which is half way through
+        let ref_date = from_ymd(2024, 10, 1);
+        let price = bond.price(&ref_date, 0.05, Convention::Street).unwrap();
+        assert_near_eq!(price.dirty_price, 102.5 / 1.0125);
+        let res = bond
+            .result(&ref_date, price.clean_price, Convention::Street)
+            .unwrap();
+        assert_near_eq!(round(res.ytm, Some(8)), 0.05);
+        assert_near_eq!(round(res.modd, Some(6)), round(0.25 / 1.0125, Some(6)));
+        let price = bond.price(&ref_date, 0.05, Convention::Compound).unwrap();
+        assert_near_eq!(price.dirty_price, 102.5 / 1.025f64.sqrt());
+        for conv in [
+            Convention::Annual,
+            Convention::Compound,
+            Convention::Street,
+            Convention::Continuous,
+        ] {
+            let res = bond.result(&ref_date, 99.0, conv).unwrap();
+            let price = bond.price(&ref_date, res.ytm, conv).unwrap();
+            assert_near_eq!(round(price.clean_price, Some(6)), 99.0);
+        }
+        assert!(to_convention("excel").is_err());
+    }
+    #[test]
+    fn china_interbank() {
+        let conv = Convention::ChinaInterbank;
+        // compounded by the coupon periods, 97 days to the next coupon in a 182-day period
+        let bond =
+            FixedBond::new(from_ymd(2020, 6, 15), from_ymd(2030, 6, 15), 100.0, 0.03, 2).unwrap();
+        let ref_date = from_ymd(2021, 3, 10);
+        let w = 97.0 / 182.0;
+        let dirty: f64 = (0..19)
+            .map(|i| {
+                let cf = if i == 18 { 101.5 } else { 1.5 };
+                cf / 1.02f64.powf(w + i as f64)
+            })
+            .sum();
+        let price = bond.price(&ref_date, 0.04, conv).unwrap();
+        assert_near_eq!(price.dirty_price, dirty);
+        let res = bond.result(&ref_date, price.clean_price, conv).unwrap();
+        assert_near_eq!(round(res.ytm, Some(8)), 0.04);
+        // the simple yield in the final coupon period, over the 365 days of the interest year
+        let bond =
+            FixedBond::new(from_ymd(2020, 6, 15), from_ymd(2025, 6, 15), 100.0, 0.03, 1).unwrap();
+        let ref_date = from_ymd(2024, 12, 1);
+        let price = bond.price(&ref_date, 0.04, conv).unwrap();
+        assert_near_eq!(price.dirty_price, 103.0 / (1.0 + 0.04 * 196.0 / 365.0));
+        let res = bond.result(&ref_date, price.clean_price, conv).unwrap();
+        assert_near_eq!(round(res.ytm, Some(8)), 0.04);
+        assert_near_eq!(
+            round(res.modd, Some(6)),
+            round(196.0 / 365.0 / (1.0 + 0.04 * 196.0 / 365.0), Some(6))
+        );
+        // the zero coupon bond is compounded annually over the ACT/ACT years to the maturity
+        let bond =
+            FixedBond::new(from_ymd(2021, 1, 1), from_ymd(2024, 1, 1), 100.0, 0.0, 0).unwrap();
+        let price = bond.price(&from_ymd(2021, 7, 1), 0.04, conv).unwrap();
+        assert_near_eq!(price.dirty_price, 100.0 / 1.04f64.powf(2.0 + 184.0 / 365.0));
+        let price = bond.price(&from_ymd(2023, 7, 1), 0.04, conv).unwrap();
+        assert_near_eq!(price.dirty_price, 100.0 / (1.0 + 0.04 * 184.0 / 365.0));
+        // the CDB bond 180210, 4.04% annual coupon from 2018-07-06 to 2028-07-06, valued at 3.60%
+        // in the CCDC style of 4-decimal clean price and accrued interest
+        let bond =
+            FixedBond::new(from_ymd(2018, 7, 6), from_ymd(2028, 7, 6), 100.0, 0.0404, 1).unwrap();
+        for (ref_date, clean_price, accrued) in [
+            // 247 days into the 2020-07-06 period, compounded over 8 coupons
+            (from_ymd(2021, 3, 10), 102.7732, 2.7339),
+            // the final coupon period of 366 days, simple yield over the 178 days left
+            (from_ymd(2028, 1, 10), 100.1746, 2.0752),
+        ] {
+            let price = bond.price(&ref_date, 0.036, conv).unwrap();
+            assert_near_eq!(round(price.clean_price, Some(4)), clean_price);
+            assert_near_eq!(round(price.accrued, Some(4)), accrued);
+            let res = bond.result(&ref_date, clean_price, conv).unwrap();
+            assert_near_eq!(round(res.ytm, Some(4)), 0.036);
+        }
+    }
+    #[test]
+    fn settlement() {
+        let calendar = Rc::new(Calendar::new(vec![from_ymd(2021, 1, 4)]));
+        let bond = FixedBond::new(from_ymd(2020, 1, 1), from_ymd(2023, 1, 1), 100.0, 0.05, 1)
+            .unwrap()
+            .with_calendar(calendar, BusDayConv::Unadjusted, false);
+        // T+0 by default
+        assert_eq!(
+            bond.settle_date(&from_ymd(2021, 1, 1)),
+            from_ymd(2021, 1, 1)
+        );
+        let bond = bond.with_settlement(1, true).unwrap();
+        // 2021-01-01 is a Friday and 2021-01-04 is a holiday
+        assert_eq!(
+            bond.settle_date(&from_ymd(2021, 1, 1)),
+            from_ymd(2021, 1, 5)
+        );
+        let bond = bond.with_settlement(2, false).unwrap();
+        assert_eq!(
+            bond.settle_date(&from_ymd(2021, 1, 1)),
+            from_ymd(2021, 1, 3)
+        );
+        assert!(bond.with_settlement(-1, true).is_err());
+    }
+    #[test]
+    fn ex_cpn() {
+        let bond = FixedBond::new(from_ymd(2020, 1, 1), from_ymd(2023, 1, 1), 100.0, 0.05, 1)
+            .unwrap()
+            .with_day_count(DayCount::Act365F)
+            .with_ex_cpn(7, true)
+            .unwrap();
+        // 7 business days before 2021-01-01 is 2020-12-23
+        let cum = from_ymd(2020, 12, 22);
+        let ex = from_ymd(2020, 12, 23);
+        assert_near_eq!(bond.accrued(&cum, true), 5.0 * 356.0 / 365.0);
+        assert_near_eq!(
+            bond.accrued(&ex, true),
+            5.0 * 357.0 / 365.0 - 5.0 * 366.0 / 365.0
+        );
+        assert_near_eq!(bond.dirty_price(&ex, 100.0), 100.0 - 5.0 * 9.0 / 365.0);
+        let cf = bond.cashflow(BondCfType::Coupon);
+        assert_near_eq!(cf.cf(&cum, None).values()[0], 5.0 * 366.0 / 365.0);
+        assert_near_eq!(cf.cf(&ex, None).values(), vec![0.0, 5.0, 5.0]);
+        let cf = bond.cashflow(BondCfType::All).cf(&ex, None);
+        assert_near_eq!(cf.values(), vec![0.0, 5.0, 105.0]);
+        let res = bond.result(&ex, 100.0, Convention::Annual).unwrap();
+        let price = bond.price(&ex, res.ytm, Convention::Annual).unwrap();
+        assert_near_eq!(round(price.clean_price, Some(8)), 100.0);
+        assert!(price.accrued < 0.0);
+        // in calendar days, it's 2020-12-25
+        let bond = FixedBond::new(from_ymd(2020, 1, 1), from_ymd(2023, 1, 1), 100.0, 0.05, 1)
+            .unwrap()
+            .with_ex_cpn(7, false)
+            .unwrap();
+        assert!(bond.accrued(&from_ymd(2020, 12, 24), true) > 0.0);
+        assert!(bond.accrued(&from_ymd(2020, 12, 25), true) < 0.0);
+        assert_eq!(bond.accrued(&from_ymd(2021, 1, 1), true), 0.0);
+        let bond = FixedBond::new(from_ymd(2020, 1, 1), from_ymd(2023, 1, 1), 100.0, 0.05, 1)
+            .unwrap()
+            .with_ex_cpn(-1, true);
+        assert!(bond.is_err());
+    }
+    #[test]
+    fn dur() {
+        let bond =
+            FixedBond::new(from_ymd(2010, 1, 1), from_ymd(2015, 1, 1), 100.0, 0.05, 0).unwrap();
         let ref_date = from_ymd(2010, 1, 1);
-        assert_eq!(rnd2(bond.result(&ref_date, 100.0).unwrap().macd), 5.0);
+        assert_eq!(
+            rnd2(
+                bond.result(&ref_date, 100.0, Convention::Annual)
+                    .unwrap()
+                    .macd
+            ),
+            5.0
+        );
         let ref_date = from_ymd(2011, 1, 1);
-        assert_eq!(rnd2(bond.result(&ref_date, 100.0).unwrap().macd), 4.0);
+        assert_eq!(
+            rnd2(
+                bond.result(&ref_date, 100.0, Convention::Annual)
+                    .unwrap()
+                    .macd
+            ),
+            4.0
+        );
         let ref_date = from_ymd(2010, 7, 1);
-        assert_eq!(rnd2(bond.result(&ref_date, 100.0).unwrap().macd), 4.5);
-
-        let bond = FixedBond {
-            value_date: from_ymd(2010, 1, 1),
-            mty_date: from_ymd(2015, 1, 1),
-            redem_value: 100.0,
-            cpn_rate: 0.05,
-            cpn_freq: to_cpn_freq(1).unwrap(),
-        };
+        assert_eq!(
+            rnd2(
+                bond.result(&ref_date, 100.0, Convention::Annual)
+                    .unwrap()
+                    .macd
+            ),
+            4.5
+        );
+
+        let bond =
+            FixedBond::new(from_ymd(2010, 1, 1), from_ymd(2015, 1, 1), 100.0, 0.05, 1).unwrap();
         let ref_date = from_ymd(2010, 1, 1);
-        let res = bond.result(&ref_date, 100.0).unwrap();
+        let res = bond.result(&ref_date, 100.0, Convention::Annual).unwrap();
         assert_eq!(rnd2(res.macd / (1.0 + res.ytm)), rnd2(res.modd));
     }
 
+    #[test]
+    fn convexity_and_dv01() {
+        // zero coupon bond has the closed form convexity t * (t + 1) / (1 + y)^2
+        let bond =
+            FixedBond::new(from_ymd(2010, 1, 1), from_ymd(2015, 1, 1), 200.0, 0.0, 0).unwrap();
+        let ref_date = from_ymd(2010, 1, 1);
+        let res = bond.result(&ref_date, 80.0, Convention::Annual).unwrap();
+        let expect = 5.0 * 6.0 / (1.0 + res.ytm).powi(2);
+        assert_near_eq!(rnd(res.cvx), rnd(expect));
+        assert_near_eq!(rnd2(res.eff_cvx), rnd2(expect));
+        // per 100 face value, i.e., the price is 40 for 100 face
+        assert_near_eq!(rnd(res.dv01 * 1e4), rnd(res.modd * 40.0));
+        assert!(res.pv01 < res.dv01);
+        assert_near_eq!(
+            rnd2(res.pv01 * 1e4),
+            rnd2(res.dv01 * 1e4 - 0.5 * res.cvx * 40.0 * 1e-4)
+        );
+    }
+    #[test]
+    fn price_from_ytm() {
+        let bond =
+            FixedBond::new(from_ymd(2010, 1, 1), from_ymd(2020, 1, 1), 100.0, 0.05, 1).unwrap();
+        let ref_date = from_ymd(2011, 1, 1);
+        let price = bond.price(&ref_date, 0.05, Convention::Annual).unwrap();
+        assert_near_eq!(rnd(price.clean_price), 100.0);
+        assert_eq!(price.accrued, 0.0);
+
+        let bond =
+            FixedBond::new(from_ymd(2010, 1, 1), from_ymd(2020, 1, 1), 100.0, 0.04, 2).unwrap();
+        let ref_date = from_ymd(2011, 3, 15);
+        let price = bond.price(&ref_date, 0.035, Convention::Annual).unwrap();
+        assert_near_eq!(price.dirty_price, price.clean_price + price.accrued);
+        assert_near_eq!(price.accrued, bond.accrued(&ref_date, true));
+        let res = bond
+            .result(&ref_date, price.clean_price, Convention::Annual)
+            .unwrap();
+        assert_near_eq!(rnd(res.ytm * 1e4), 350.0);
+        let price2 = bond.price(&ref_date, res.ytm, Convention::Annual).unwrap();
+        assert_near_eq!(rnd(price2.clean_price), rnd(price.clean_price));
+
+        let ref_date = from_ymd(2020, 1, 1);
+        assert!(bond.price(&ref_date, 0.035, Convention::Annual).is_none());
+    }
+    #[test]
+    fn horizon_return() {
+        let ref_date = from_ymd(2021, 1, 1);
+        let horizon_date = from_ymd(2022, 1, 1);
+        let conv = Convention::Annual;
+        // the par bond at the unchanged yield earns the coupon only
+        let bond =
+            FixedBond::new(from_ymd(2020, 1, 1), from_ymd(2025, 1, 1), 100.0, 0.05, 1).unwrap();
+        let res = bond
+            .horizon_return(&ref_date, 100.0, &horizon_date, 0.03, None, 0.0, conv)
+            .unwrap();
+        assert_near_eq!(res.income, 0.05);
+        assert_eq!(res.reinvest, 0.0);
+        assert!(res.roll_down.abs() < 1e-6);
+        assert!(res.price_chg.abs() < 1e-6);
+        assert!((res.total - 0.05).abs() < 1e-6);
+        assert!((res.horizon_price.unwrap() - 100.0).abs() < 1e-4);
+        // the exit yield widens by 1%
+        let res = bond
+            .horizon_return(&ref_date, 100.0, &horizon_date, 0.03, None, 0.01, conv)
+            .unwrap();
+        let exit_price = bond.price(&horizon_date, 0.06, conv).unwrap();
+        assert!((res.price_chg - (exit_price.dirty_price - 100.0) / 100.0).abs() < 1e-6);
+        assert!((res.horizon_price.unwrap() - exit_price.clean_price).abs() < 1e-6);
+        let res2 = bond
+            .horizon_return(&ref_date, 100.0, &horizon_date, 0.03, Some(0.06), 0.0, conv)
+            .unwrap();
+        assert!((res2.total - res.total).abs() < 1e-6);
+        // the zero coupon bond rolls down to the par at its yield
+        let bond =
+            FixedBond::new(from_ymd(2020, 1, 1), from_ymd(2025, 1, 1), 100.0, 0.0, 0).unwrap();
+        let ytm = bond.result(&ref_date, 90.0, conv).unwrap().ytm;
+        let res = bond
+            .horizon_return(&ref_date, 90.0, &horizon_date, 0.03, None, 0.0, conv)
+            .unwrap();
+        assert_eq!(res.income, 0.0);
+        assert!((res.roll_down - ytm).abs() < 1e-6);
+        assert!((res.total - ytm).abs() < 1e-6);
+        // the mid-year coupon is reinvested for half a year
+        let bond =
+            FixedBond::new(from_ymd(2020, 1, 1), from_ymd(2025, 1, 1), 100.0, 0.04, 2).unwrap();
+        let res = bond
+            .horizon_return(&ref_date, 100.0, &horizon_date, 0.03, None, 0.0, conv)
+            .unwrap();
+        assert_near_eq!(res.income, 0.04);
+        assert_near_eq!(res.reinvest, 2.0 * (1.03f64.sqrt() - 1.0) / 100.0);
+        // all the cashflows are reinvested after the maturity
+        let bond =
+            FixedBond::new(from_ymd(2020, 1, 1), from_ymd(2022, 1, 1), 100.0, 0.05, 1).unwrap();
+        let res = bond
+            .horizon_return(
+                &ref_date,
+                100.0,
+                &from_ymd(2023, 1, 1),
+                0.02,
+                None,
+                0.0,
+                conv,
+            )
+            .unwrap();
+        assert_near_eq!(res.total, 105.0 * 1.02 / 100.0 - 1.0);
+        assert!(res.horizon_price.is_none());
+        assert!(bond
+            .horizon_return(&ref_date, 100.0, &ref_date, 0.02, None, 0.0, conv)
+            .is_none());
+    }
+    #[test]
+    fn carry_roll() {
+        let ref_date = from_ymd(2021, 1, 1);
+        let horizon_date = from_ymd(2021, 4, 1);
+        let bond =
+            FixedBond::new(from_ymd(2020, 1, 1), from_ymd(2026, 1, 1), 100.0, 0.04, 1).unwrap();
+        // the bond on the flat curve earns the coupon over the funding cost only
+        let flat = ZeroCurve::new(vec![1.0], vec![0.04]).unwrap();
+        let res = bond
+            .carry_roll(&ref_date, 100.0, &horizon_date, 0.02, &flat, &ref_date)
+            .unwrap();
+        let accrued = bond.accrued(&horizon_date, true);
+        assert_near_eq!(res.carry, accrued - 100.0 * 0.02 * 90.0 / 365.0);
+        // the clean price at the unchanged continuously compounded yield, i.e., the flat zero
+        // rate plus the Z-spread
+        let spread = bond.z_spread(&ref_date, 100.0, &flat, &ref_date).unwrap();
+        let unchanged = bond
+            .price(&horizon_date, 0.04 + spread, Convention::Continuous)
+            .unwrap();
+        assert_near_eq!(res.roll_down, unchanged.clean_price - 100.0);
+        // the price falls by about the dollar duration times the breakeven
+        let dollar_dur = bond
+            .result(&horizon_date, unchanged.clean_price, Convention::Continuous)
+            .map(|v| v.modd * unchanged.dirty_price)
+            .unwrap();
+        let approx = (res.carry + res.roll_down) / dollar_dur;
+        assert!((res.breakeven - approx).abs() < 1e-2 * approx);
+        // rolls down the upward sloping curve, keeping the Z-spread on the rolled curve
+        let curve = ZeroCurve::new(vec![1.0, 5.0], vec![0.02, 0.04]).unwrap();
+        let res2 = bond
+            .carry_roll(&ref_date, 100.0, &horizon_date, 0.02, &curve, &ref_date)
+            .unwrap();
+        assert_near_eq!(res2.carry, res.carry);
+        let spread = bond.z_spread(&ref_date, 100.0, &curve, &ref_date).unwrap();
+        let rolled = bond.z_spread(&horizon_date, 100.0 + res2.roll_down, &curve, &horizon_date);
+        assert_near_eq!(round(rolled.unwrap(), Some(8)), round(spread, Some(8)));
+        assert!(res2.roll_down > res.roll_down);
+        assert!(res2.breakeven > res.breakeven);
+        // the same when the curve is as of an earlier date
+        let curve_date = from_ymd(2020, 12, 1);
+        let res3 = bond
+            .carry_roll(&ref_date, 100.0, &horizon_date, 0.02, &flat, &curve_date)
+            .unwrap();
+        assert_near_eq!(res3.roll_down, res.roll_down);
+        assert!(bond
+            .carry_roll(
+                &ref_date,
+                100.0,
+                &from_ymd(2027, 1, 1),
+                0.02,
+                &curve,
+                &ref_date
+            )
+            .is_none());
+    }
     #[test]
     fn none_if_xirr_fail() {
-        let bond = FixedBond {
-            value_date: from_ymd(2012, 1, 1),
-            mty_date: from_ymd(2015, 1, 1),
-            redem_value: 100.0,
-            cpn_rate: 0.05,
-            cpn_freq: to_cpn_freq(1).unwrap(),
-        };
+        let bond =
+            FixedBond::new(from_ymd(2012, 1, 1), from_ymd(2015, 1, 1), 100.0, 0.05, 1).unwrap();
         let ref_date = from_ymd(2016, 1, 1);
-        let res = bond.result(&ref_date, 100.0);
+        let res = bond.result(&ref_date, 100.0, Convention::Annual);
         assert!(res.is_none());
-        let bond = FixedBond {
-            value_date: from_ymd(2018, 1, 1),
-            mty_date: from_ymd(2015, 1, 1),
-            redem_value: 100.0,
-            cpn_rate: 0.05,
-            cpn_freq: to_cpn_freq(1).unwrap(),
-        };
+        let bond =
+            FixedBond::new(from_ymd(2018, 1, 1), from_ymd(2015, 1, 1), 100.0, 0.05, 1).unwrap();
         let ref_date = from_ymd(2016, 1, 1);
-        let res = bond.result(&ref_date, 100.0);
+        let res = bond.result(&ref_date, 100.0, Convention::Annual);
         assert!(res.is_none());
     }
     #[test]
@@ -0,0 +1,156 @@
+use crate::bond::{BondCfType, FixedBond};
+use crate::curve::{Interp, ZeroCurve};
+use crate::date_handle;
+use crate::find_root::find_root_in;
+use chrono::NaiveDate;
+
+// The passes over the pillars until the rates converge, as the non-local interpolations make
+// the later pillars affect the earlier instruments
+const MAX_PASSES: usize = 50;
+const TOLERANCE: f64 = 1e-10;
+
+// The pillar date, the cashflows keyed by the year fractions and the price of an instrument
+type Pillar = (NaiveDate, Vec<(f64, f64)>, f64);
+
+// The market instruments that the discount curve is bootstrapped from
+#[derive(Debug)]
+pub enum Instrument<'a> {
+    // the deposit maturing on the date, quoted as the simple rate over ACT/365F
+    Deposit(NaiveDate, f64),
+    // the bond and its dirty price, e.g., a par bond priced at its face value
+    Bond(&'a FixedBond, f64),
+}
+
+impl Instrument<'_> {
+    // The cashflows after `ref_date` keyed by the dates, and the price they are discounted to
+    fn cashflows(&self, ref_date: &NaiveDate) -> (Vec<(NaiveDate, f64)>, f64) {
+        match self {
+            Instrument::Deposit(date, rate) => {
+                let days = (*date - *ref_date).num_days() as f64;
+                (vec![(*date, 1.0 + rate * days / 365.0)], 1.0)
+            }
+            Instrument::Bond(bond, dirty_price) => {
+                let cf = bond.cashflow(BondCfType::All).cf(ref_date, None);
+                let cfs = cf.dates().into_iter().zip(cf.values()).collect();
+                (cfs, *dirty_price)
+            }
+        }
+    }
+}
+
+// The zero curve as of `ref_date` that reprices all the `instruments`, with a pillar on the
+// last cashflow date of each. It returns the pillar dates and the curve.
+pub fn bootstrap(
+    ref_date: &NaiveDate,
+    instruments: &[Instrument],
+    interp: Interp,
+) -> Result<(Vec<NaiveDate>, ZeroCurve), String> {
+    let mut items: Vec<Pillar> = Vec::new();
+    for instrument in instruments {
+        let (cfs, price) = instrument.cashflows(ref_date);
+        let pillar = match cfs.last() {
+            Some((date, _)) if date > ref_date => *date,
+            _ => return Err(format!("{:?} has no cashflow after ref_date", instrument)),
+        };
+        let cfs = cfs
+            .into_iter()
+            .map(|(date, cf)| (date_handle::year_frac(&date, ref_date), cf))
+            .collect();
+        items.push((pillar, cfs, price));
+    }
+    items.sort_by_key(|item| item.0);
+    if let Some(w) = items.windows(2).find(|w| w[0].0 == w[1].0) {
+        return Err(format!("more than one instrument matures on {}", w[0].0));
+    }
+    let pillars: Vec<NaiveDate> = items.iter().map(|item| item.0).collect();
+    let tenors: Vec<f64> = pillars
+        .iter()
+        .map(|date| date_handle::year_frac(date, ref_date))
+        .collect();
+    let n = items.len();
+    if n == 0 {
+        return Err("instruments must not be empty".to_string());
+    }
+    let mut rates = vec![0.0; n];
+    for pass in 0..MAX_PASSES {
+        let mut change = 0.0f64;
+        for (i, (pillar, cfs, price)) in items.iter().enumerate() {
+            // only the pillars so far are known in the first pass
+            let m = if pass == 0 { i + 1 } else { n };
+            let pv = |rate: f64| {
+                let mut rates = rates[..m].to_vec();
+                rates[i] = rate;
+                let curve = ZeroCurve::new(tenors[..m].to_vec(), rates)
+                    .unwrap()
+                    .with_interp(interp);
+                cfs.iter().map(|(t, cf)| cf * curve.df(*t)).sum::<f64>() - price
+            };
+            let guess = if pass == 0 && i > 0 {
+                rates[i - 1]
+            } else {
+                rates[i]
+            };
+            let rate = find_root_in(guess, -1.0, 1.0, pv)
+                .ok_or_else(|| format!("failed to bootstrap the pillar on {}", pillar))?;
+            change = change.max((rate - rates[i]).abs());
+            rates[i] = rate;
+        }
+        if pass > 0 && change < TOLERANCE {
+            break;
+        }
+    }
+    let curve = ZeroCurve::new(tenors, rates)?.with_interp(interp);
+    Ok((pillars, curve))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    fn from_ymd(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).expect("invalid or out-of-range date")
+    }
+    #[test]
+    fn reprice_instruments() {
+        let ref_date = from_ymd(2021, 1, 1);
+        let par2y = FixedBond::new(ref_date, from_ymd(2023, 1, 1), 100.0, 0.025, 1).unwrap();
+        let par5y = FixedBond::new(ref_date, from_ymd(2026, 1, 1), 100.0, 0.03, 2).unwrap();
+        let bond =
+            FixedBond::new(from_ymd(2020, 7, 1), from_ymd(2031, 1, 1), 100.0, 0.04, 1).unwrap();
+        let dirty_price = bond.dirty_price(&ref_date, 105.0);
+        let instruments = [
+            Instrument::Bond(&par5y, 100.0),
+            Instrument::Deposit(from_ymd(2021, 7, 1), 0.02),
+            Instrument::Bond(&par2y, 100.0),
+            Instrument::Bond(&bond, dirty_price),
+        ];
+        for interp in [
+            Interp::LinearZero,
+            Interp::LogLinearDf,
+            Interp::MonotoneConvex,
+            Interp::CubicSpline,
+        ] {
+            let (pillars, curve) = bootstrap(&ref_date, &instruments, interp).unwrap();
+            assert_eq!(pillars[0], from_ymd(2021, 7, 1));
+            assert_eq!(pillars.len(), 4);
+            for instrument in &instruments {
+                let (cfs, price) = instrument.cashflows(&ref_date);
+                let pv: f64 = cfs
+                    .iter()
+                    .map(|(date, cf)| cf * curve.df(date_handle::year_frac(date, &ref_date)))
+                    .sum();
+                assert!((pv - price).abs() < 1e-6);
+            }
+        }
+        let deposit = Instrument::Deposit(from_ymd(2021, 7, 1), 0.02);
+        let (_, curve) = bootstrap(&ref_date, &[deposit], Interp::LinearZero).unwrap();
+        let t = date_handle::year_frac(&from_ymd(2021, 7, 1), &ref_date);
+        assert!((curve.df(t) - 1.0 / (1.0 + 0.02 * 181.0 / 365.0)).abs() < 1e-10);
+        let duplicated = [
+            Instrument::Deposit(from_ymd(2023, 1, 1), 0.02),
+            Instrument::Bond(&par2y, 100.0),
+        ];
+        assert!(bootstrap(&ref_date, &duplicated, Interp::LinearZero).is_err());
+        let expired = [Instrument::Deposit(ref_date, 0.02)];
+        assert!(bootstrap(&ref_date, &expired, Interp::LinearZero).is_err());
+    }
+}
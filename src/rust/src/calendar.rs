@@ -0,0 +1,121 @@
+use chrono::{Datelike, NaiveDate, Weekday};
+use std::collections::BTreeSet;
+
+// The business day convention used to move a date that is not a business day
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum BusDayConv {
+    Following,
+    ModifiedFollowing,
+    Preceding,
+    Unadjusted,
+}
+
+pub fn to_bus_day_conv(bdc: &str) -> Result<BusDayConv, String> {
+    match bdc.to_lowercase().as_str() {
+        "following" => Ok(BusDayConv::Following),
+        "modified_following" => Ok(BusDayConv::ModifiedFollowing),
+        "preceding" => Ok(BusDayConv::Preceding),
+        "unadjusted" => Ok(BusDayConv::Unadjusted),
+        _ => Err(format!("bdc({}) is undefined", bdc)),
+    }
+}
+
+// A business day calendar, where weekends and the provided holidays are not business days
+#[derive(Debug, Default)]
+pub struct Calendar {
+    holidays: BTreeSet<NaiveDate>,
+}
+
+impl Calendar {
+    pub fn new(holidays: Vec<NaiveDate>) -> Self {
+        Self {
+            holidays: holidays.into_iter().collect(),
+        }
+    }
+    pub fn is_bizday(&self, date: &NaiveDate) -> bool {
+        !matches!(date.weekday(), Weekday::Sat | Weekday::Sun) && !self.holidays.contains(date)
+    }
+    fn nxt_bizday(&self, date: &NaiveDate, forward: bool) -> NaiveDate {
+        let mut date = *date;
+        while !self.is_bizday(&date) {
+            date = if forward {
+                date.succ_opt().unwrap()
+            } else {
+                date.pred_opt().unwrap()
+            };
+        }
+        date
+    }
+    // Move `date` by `n` business days, backward if `n` is negative
+    pub fn add_bizdays(&self, date: &NaiveDate, n: i64) -> NaiveDate {
+        let mut date = *date;
+        let mut n = n;
+        while n != 0 {
+            date = if n > 0 {
+                date.succ_opt().unwrap()
+            } else {
+                date.pred_opt().unwrap()
+            };
+            if self.is_bizday(&date) {
+                n -= n.signum();
+            }
+        }
+        date
+    }
+    pub fn adjust(&self, date: &NaiveDate, bdc: BusDayConv) -> NaiveDate {
+        match bdc {
+            BusDayConv::Unadjusted => *date,
+            BusDayConv::Following => self.nxt_bizday(date, true),
+            BusDayConv::Preceding => self.nxt_bizday(date, false),
+            BusDayConv::ModifiedFollowing => {
+                let adjusted = self.nxt_bizday(date, true);
+                if adjusted.month() != date.month() {
+                    self.nxt_bizday(date, false)
+                } else {
+                    adjusted
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    fn from_ymd(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).expect("invalid or out-of-range date")
+    }
+    #[test]
+    fn adjust_work() {
+        // 2022-04-30 is Saturday, 2022-05-02 is a holiday
+        let cal = Calendar::new(vec![from_ymd(2022, 5, 2)]);
+        let date = from_ymd(2022, 4, 30);
+        assert!(!cal.is_bizday(&date));
+        assert_eq!(cal.adjust(&date, BusDayConv::Unadjusted), date);
+        assert_eq!(
+            cal.adjust(&date, BusDayConv::Following),
+            from_ymd(2022, 5, 3)
+        );
+        assert_eq!(
+            cal.adjust(&date, BusDayConv::Preceding),
+            from_ymd(2022, 4, 29)
+        );
+        assert_eq!(
+            cal.adjust(&date, BusDayConv::ModifiedFollowing),
+            from_ymd(2022, 4, 29)
+        );
+        let date = from_ymd(2022, 1, 1);
+        assert_eq!(
+            cal.adjust(&date, BusDayConv::ModifiedFollowing),
+            from_ymd(2022, 1, 3)
+        );
+        assert_eq!(cal.add_bizdays(&date, 1), from_ymd(2022, 1, 3));
+        assert_eq!(
+            cal.add_bizdays(&from_ymd(2022, 5, 3), -2),
+            from_ymd(2022, 4, 28)
+        );
+        assert_eq!(cal.add_bizdays(&date, 0), date);
+        assert!(to_bus_day_conv("Modified_Following").is_ok());
+        assert!(to_bus_day_conv("nearest").is_err());
+    }
+}
@@ -0,0 +1,351 @@
+// The interpolation of the zero curve between the tenors
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Interp {
+    // linear on the zero rates
+    LinearZero,
+    // linear on the log discount factors, i.e., flat forward rates between the tenors
+    LogLinearDf,
+    // the monotone convex method of Hagan and West (2006), on the forward rates
+    MonotoneConvex,
+    // the natural cubic spline on the zero rates
+    CubicSpline,
+}
+
+pub fn to_interp(interp: &str) -> Result<Interp, String> {
+    match interp.to_lowercase().as_str() {
+        "linear_zero" | "linear" => Ok(Interp::LinearZero),
+        "log_linear_df" | "log_linear" => Ok(Interp::LogLinearDf),
+        "monotone_convex" => Ok(Interp::MonotoneConvex),
+        "cubic_spline" | "spline" => Ok(Interp::CubicSpline),
+        _ => Err(format!("interp({}) is undefined", interp)),
+    }
+}
+
+// The zero curve of continuously compounded rates, keyed by the tenors in years. The rates
+// between the tenors are interpolated by `interp` (linear by default) and flat extrapolated
+// outside, except that the monotone convex method starts from the tenor zero.
+#[derive(Debug, Clone)]
+pub struct ZeroCurve {
+    tenors: Vec<f64>,
+    rates: Vec<f64>,
+    interp: Interp,
+    // the 2nd derivatives of the cubic spline, or the instantaneous forward rates on zero and
+    // the tenors of the monotone convex method
+    coefs: Vec<f64>,
+}
+
+impl ZeroCurve {
+    pub fn new(tenors: Vec<f64>, rates: Vec<f64>) -> Result<Self, String> {
+        if tenors.is_empty() || tenors.len() != rates.len() {
+            return Err("tenors and rates must be non-empty and the same length".to_string());
+        }
+        if tenors.windows(2).any(|w| w[0] >= w[1]) {
+            return Err("tenors must be strictly increasing".to_string());
+        }
+        if tenors.iter().chain(rates.iter()).any(|v| !v.is_finite()) {
+            return Err("tenors and rates must be finite".to_string());
+        }
+        Ok(Self {
+            tenors,
+            rates,
+            interp: Interp::LinearZero,
+            coefs: Vec::new(),
+        })
+    }
+    pub fn with_interp(mut self, interp: Interp) -> Self {
+        self.interp = interp;
+        self.coefs = match interp {
+            Interp::LinearZero | Interp::LogLinearDf => Vec::new(),
+            Interp::CubicSpline => self.spline_coefs(),
+            Interp::MonotoneConvex => self.node_fwds(),
+        };
+        self
+    }
+    pub fn tenors(&self) -> &[f64] {
+        &self.tenors
+    }
+    pub fn rates(&self) -> &[f64] {
+        &self.rates
+    }
+    pub fn zero(&self, t: f64) -> f64 {
+        if self.interp == Interp::MonotoneConvex && t > 0.0 && t < self.tenors[0] {
+            return self.monotone_convex(t);
+        }
+        let i = self.tenors.partition_point(|tenor| tenor < &t);
+        if i == 0 {
+            self.rates[0]
+        } else if i == self.tenors.len() {
+            self.rates[i - 1]
+        } else {
+            let (t0, t1) = (self.tenors[i - 1], self.tenors[i]);
+            let (r0, r1) = (self.rates[i - 1], self.rates[i]);
+            let w = (t - t0) / (t1 - t0);
+            match self.interp {
+                Interp::LinearZero => r0 + w * (r1 - r0),
+                Interp::LogLinearDf => ((1.0 - w) * r0 * t0 + w * r1 * t1) / t,
+                Interp::CubicSpline => {
+                    let (a, b) = (1.0 - w, w);
+                    let (m0, m1) = (self.coefs[i - 1], self.coefs[i]);
+                    a * r0
+                        + b * r1
+                        + ((a * a * a - a) * m0 + (b * b * b - b) * m1) * (t1 - t0).powi(2) / 6.0
+                }
+                Interp::MonotoneConvex => self.monotone_convex(t),
+            }
+        }
+    }
+    pub fn df(&self, t: f64) -> f64 {
+        (-self.zero(t) * t).exp()
+    }
+    // The continuously compounded forward rate from `t0` to `t1`, or the instantaneous one if
+    // they are the same
+    pub fn fwd(&self, t0: f64, t1: f64) -> f64 {
+        let t1 = if t1 - t0 < 1e-6 { t0 + 1e-6 } else { t1 };
+        (self.zero(t1) * t1 - self.zero(t0) * t0) / (t1 - t0)
+    }
+    // The curve with all the rates shifted in parallel by `shift`
+    pub fn shift(&self, shift: f64) -> Self {
+        Self {
+            tenors: self.tenors.clone(),
+            rates: self.rates.iter().map(|r| r + shift).collect(),
+            interp: Interp::LinearZero,
+            coefs: Vec::new(),
+        }
+        .with_interp(self.interp)
+    }
+    // The curve as of `t0` years later implied by the forward rates up to `horizon` years from
+    // then, sampled monthly and on the tenors, with flat forwards in between
+    pub fn roll(&self, t0: f64, horizon: f64) -> Self {
+        let mut tenors: Vec<f64> = (1..)
+            .map(|m| m as f64 / 12.0)
+            .take_while(|t| *t < horizon)
+            .chain(std::iter::once(horizon))
+            .chain(self.tenors.iter().map(|t| t - t0))
+            .filter(|t| *t > 0.0 && *t <= horizon)
+            .collect();
+        tenors.sort_by(|a, b| a.total_cmp(b));
+        tenors.dedup_by(|a, b| (*a - *b).abs() < 1e-8);
+        if tenors.is_empty() {
+            tenors.push(1.0);
+        }
+        let rates = tenors.iter().map(|t| self.fwd(t0, t0 + t)).collect();
+        Self {
+            tenors,
+            rates,
+            interp: Interp::LinearZero,
+            coefs: Vec::new(),
+        }
+        .with_interp(Interp::LogLinearDf)
+    }
+    // The natural cubic spline, solved by the tridiagonal algorithm
+    fn spline_coefs(&self) -> Vec<f64> {
+        let n = self.tenors.len();
+        let mut coefs = vec![0.0; n];
+        if n < 3 {
+            return coefs;
+        }
+        let (x, y) = (&self.tenors, &self.rates);
+        let mut diag = vec![0.0; n];
+        let mut rhs = vec![0.0; n];
+        let mut upper = vec![0.0; n];
+        for i in 1..n - 1 {
+            let (h0, h1) = (x[i] - x[i - 1], x[i + 1] - x[i]);
+            let lower = h0 / 6.0;
+            let mut d = (h0 + h1) / 3.0;
+            let mut r = (y[i + 1] - y[i]) / h1 - (y[i] - y[i - 1]) / h0;
+            if i > 1 {
+                let k = lower / diag[i - 1];
+                d -= k * upper[i - 1];
+                r -= k * rhs[i - 1];
+            }
+            diag[i] = d;
+            rhs[i] = r;
+            upper[i] = h1 / 6.0;
+        }
+        for i in (1..n - 1).rev() {
+            coefs[i] = (rhs[i] - upper[i] * coefs[i + 1]) / diag[i];
+        }
+        coefs
+    }
+    // The discrete forward rates of the periods between zero and the tenors
+    fn discrete_fwds(&self) -> Vec<f64> {
+        let mut prev = (0.0, 0.0);
+        self.tenors
+            .iter()
+            .zip(&self.rates)
+            .map(|(t, r)| {
+                let fwd = (r * t - prev.1) / (t - prev.0);
+                prev = (*t, r * t);
+                fwd
+            })
+            .collect()
+    }
+    fn node(&self, i: usize) -> f64 {
+        if i == 0 {
+            0.0
+        } else {
+            self.tenors[i - 1]
+        }
+    }
+    fn node_fwds(&self) -> Vec<f64> {
+        let fd = self.discrete_fwds();
+        let n = fd.len();
+        let mut f = vec![fd[0]; n + 1];
+        for i in 1..n {
+            let (t0, t1, t2) = (self.node(i - 1), self.node(i), self.node(i + 1));
+            f[i] = (t1 - t0) / (t2 - t0) * fd[i] + (t2 - t1) / (t2 - t0) * fd[i - 1];
+        }
+        if n > 1 {
+            f[0] = fd[0] - 0.5 * (f[1] - fd[0]);
+            f[n] = fd[n - 1] - 0.5 * (f[n - 1] - fd[n - 1]);
+        }
+        f
+    }
+    // The zero rate of the monotone convex method, which integrates the instantaneous forward
+    // rates. Within each period, they are the discrete forward rate plus g(x), which has zero
+    // mean and stays monotone between the forward rates on the nodes.
+    fn monotone_convex(&self, t: f64) -> f64 {
+        let i = self.tenors.partition_point(|tenor| tenor < &t);
+        let (t0, t1) = (self.node(i), self.node(i + 1));
+        let fd =
+            (self.rates[i] * t1 - if i == 0 { 0.0 } else { self.rates[i - 1] * t0 }) / (t1 - t0);
+        let g0 = self.coefs[i] - fd;
+        let g1 = self.coefs[i + 1] - fd;
+        let x = (t - t0) / (t1 - t0);
+        // the integral of g from 0 to x
+        let integral = if g0 == 0.0 && g1 == 0.0 {
+            0.0
+        } else if (g0 < 0.0 && g1 > -2.0 * g0) || (g0 > 0.0 && g1 < -2.0 * g0) {
+            let eta = (g1 + 2.0 * g0) / (g1 - g0);
+            if x <= eta {
+                g0 * x
+            } else {
+                g0 * x + (g1 - g0) * (x - eta).powi(3) / (3.0 * (1.0 - eta).powi(2))
+            }
+        } else if (g0 > 0.0 && g1 < 0.0 && g1 > -0.5 * g0)
+            || (g0 < 0.0 && g1 > 0.0 && g1 < -0.5 * g0)
+        {
+            let eta = 3.0 * g1 / (g1 - g0);
+            if x < eta {
+                g1 * x + (g0 - g1) * (eta.powi(3) - (eta - x).powi(3)) / (3.0 * eta * eta)
+            } else {
+                g1 * x + (g0 - g1) * eta / 3.0
+            }
+        } else if g0 * g1 > 0.0 {
+            let eta = g1 / (g1 + g0);
+            let a = -g0 * g1 / (g0 + g1);
+            if x <= eta {
+                a * x + (g0 - a) * (eta.powi(3) - (eta - x).powi(3)) / (3.0 * eta * eta)
+            } else {
+                a * x
+                    + (g0 - a) * eta / 3.0
+                    + (g1 - a) * (x - eta).powi(3) / (3.0 * (1.0 - eta).powi(2))
+            }
+        } else {
+            g0 * (x - 2.0 * x * x + x * x * x) + g1 * (x * x * x - x * x)
+        };
+        let rt = if i == 0 { 0.0 } else { self.rates[i - 1] * t0 };
+        (rt + fd * (t - t0) + (t1 - t0) * integral) / t
+    }
+}
+
+// The triangular shock of the `k`-th key rate tenor at `t`, which is 1 on the key tenor and
+// falls linearly to 0 on the neighboring ones. It's flat beyond the first and the last key
+// tenor, so that the shocks of all the key tenors add up to a parallel shift.
+pub fn key_rate_shock(tenors: &[f64], k: usize, t: f64) -> f64 {
+    let n = tenors.len();
+    if t <= tenors[k] {
+        match k {
+            0 => 1.0,
+            _ => ((t - tenors[k - 1]) / (tenors[k] - tenors[k - 1])).max(0.0),
+        }
+    } else if k + 1 == n {
+        1.0
+    } else {
+        ((tenors[k + 1] - t) / (tenors[k + 1] - tenors[k])).max(0.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assert::NearEq;
+    #[test]
+    fn zero_curve() {
+        let curve = ZeroCurve::new(vec![1.0, 3.0], vec![0.02, 0.04]).unwrap();
+        assert_near_eq!(curve.zero(0.5), 0.02);
+        assert_near_eq!(curve.zero(2.0), 0.03);
+        assert_near_eq!(curve.zero(5.0), 0.04);
+        assert_near_eq!(curve.df(2.0), (-0.06f64).exp());
+        assert_near_eq!(curve.df(0.0), 1.0);
+        assert_near_eq!(curve.shift(0.01).zero(2.0), 0.04);
+        assert!(ZeroCurve::new(vec![3.0, 1.0], vec![0.02, 0.04]).is_err());
+        assert!(ZeroCurve::new(vec![1.0], vec![0.02, 0.04]).is_err());
+        // the rolled curve discounts with the forward rates, also beyond the last tenor
+        for t0 in [-0.5, 0.0, 1.25, 4.0] {
+            let rolled = curve.roll(t0, 3.0);
+            for t in [0.5, 0.75, 1.75, 3.0] {
+                assert_near_eq!(rolled.df(t), curve.df(t0 + t) / curve.df(t0));
+            }
+        }
+    }
+    #[test]
+    fn key_rate_shocks() {
+        let tenors = [1.0, 2.0, 5.0];
+        assert_near_eq!(key_rate_shock(&tenors, 0, 0.5), 1.0);
+        assert_near_eq!(key_rate_shock(&tenors, 1, 0.5), 0.0);
+        assert_near_eq!(key_rate_shock(&tenors, 1, 3.5), 0.5);
+        assert_near_eq!(key_rate_shock(&tenors, 2, 3.5), 0.5);
+        assert_near_eq!(key_rate_shock(&tenors, 2, 30.0), 1.0);
+        for t in [0.0, 1.5, 2.0, 4.0, 10.0] {
+            let total: f64 = (0..3).map(|k| key_rate_shock(&tenors, k, t)).sum();
+            assert_near_eq!(total, 1.0);
+        }
+    }
+    #[test]
+    fn interpolation() {
+        let tenors = vec![1.0, 2.0, 5.0, 10.0];
+        let rates = vec![0.02, 0.025, 0.03, 0.028];
+        let new = |interp| {
+            ZeroCurve::new(tenors.clone(), rates.clone())
+                .unwrap()
+                .with_interp(interp)
+        };
+        for interp in [
+            Interp::LinearZero,
+            Interp::LogLinearDf,
+            Interp::MonotoneConvex,
+            Interp::CubicSpline,
+        ] {
+            let curve = new(interp);
+            // all of them go through the pillars
+            for (t, r) in tenors.iter().zip(&rates) {
+                assert_near_eq!(round(curve.zero(*t)), *r);
+            }
+            assert_near_eq!(round(curve.shift(0.01).zero(5.0)), 0.04);
+        }
+        // flat forward between the pillars
+        let curve = new(Interp::LogLinearDf);
+        assert_near_eq!(round(curve.fwd(2.5, 3.0)), round(curve.fwd(3.5, 4.5)));
+        assert_near_eq!(round(curve.fwd(2.0, 5.0)), round((0.15 - 0.05) / 3.0));
+        // the monotone convex keeps the discrete forward rates and is continuous in forwards
+        let curve = new(Interp::MonotoneConvex);
+        assert_near_eq!(round(curve.fwd(2.0, 5.0)), round((0.15 - 0.05) / 3.0));
+        assert!((curve.fwd(5.0 - 1e-4, 5.0 - 1e-4) - curve.fwd(5.0, 5.0)).abs() < 1e-4);
+        // the natural spline is smooth but not linear
+        let curve = new(Interp::CubicSpline);
+        assert!((curve.zero(3.5) - 0.0275).abs() > 1e-5);
+        let flat = ZeroCurve::new(vec![1.0, 2.0, 3.0], vec![0.03; 3])
+            .unwrap()
+            .with_interp(Interp::CubicSpline);
+        assert_near_eq!(flat.zero(2.5), 0.03);
+        let curve = ZeroCurve::new(vec![1.0], vec![0.03])
+            .unwrap()
+            .with_interp(Interp::MonotoneConvex);
+        assert_near_eq!(round(curve.zero(0.5)), 0.03);
+        assert!(to_interp("nearest").is_err());
+    }
+    fn round(x: f64) -> f64 {
+        (x * 1e10).round() / 1e10
+    }
+}
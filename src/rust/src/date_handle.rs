@@ -24,6 +24,25 @@ pub fn add_months(ref_date: &NaiveDate, months: i32) -> NaiveDate {
     )
 }
 
+pub fn is_month_end(date: &NaiveDate) -> bool {
+    date.succ_opt().map_or(true, |d| d.month() != date.month())
+}
+
+// Same as `add_months()`, except that it sticks to the month end when `eom` is true
+// and `ref_date` is a month end, e.g., 2021-04-30 + 6 months is 2021-10-31
+pub fn roll_months(ref_date: &NaiveDate, months: i32, eom: bool) -> NaiveDate {
+    let date = add_months(ref_date, months);
+    if eom && is_month_end(ref_date) {
+        let mut date = date;
+        while !is_month_end(&date) {
+            date = date.succ_opt().unwrap();
+        }
+        date
+    } else {
+        date
+    }
+}
+
 // this is similiar to 30 / 360 rule
 pub fn year_frac(d1: &NaiveDate, d0: &NaiveDate) -> f64 {
     (d1.year() - d0.year()) as f64
@@ -44,4 +63,15 @@ mod tests {
         assert_eq!(add_months(&ref_date, 11), from_ymd(2021, 11, 30));
         assert_eq!(add_months(&ref_date, 12), from_ymd(2021, 12, 31));
     }
+    #[test]
+    fn roll_months_work() {
+        let ref_date = from_ymd(2021, 2, 28);
+        assert_eq!(roll_months(&ref_date, 6, false), from_ymd(2021, 8, 28));
+        assert_eq!(roll_months(&ref_date, 6, true), from_ymd(2021, 8, 31));
+        assert_eq!(roll_months(&ref_date, -12, true), from_ymd(2020, 2, 29));
+        let ref_date = from_ymd(2021, 4, 30);
+        assert_eq!(roll_months(&ref_date, 6, true), from_ymd(2021, 10, 31));
+        let ref_date = from_ymd(2021, 4, 29);
+        assert_eq!(roll_months(&ref_date, 6, true), from_ymd(2021, 10, 29));
+    }
 }
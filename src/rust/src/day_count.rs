@@ -0,0 +1,231 @@
+use crate::date_handle;
+use crate::date_handle::is_month_end;
+use chrono::{Datelike, NaiveDate};
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum DayCount {
+    ActActIcma,
+    ActActIsda,
+    Act360,
+    Act365F,
+    Thirty360Us,
+    Thirty360E,
+    Thirty360EIsda,
+    NL365,
+}
+
+pub fn to_day_count(day_count: &str) -> Result<DayCount, String> {
+    let key: String = day_count
+        .to_uppercase()
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect();
+    match key.as_str() {
+        "ACT/ACT" | "ACT/ACTICMA" | "ACT/ACTISMA" => Ok(DayCount::ActActIcma),
+        "ACT/ACTISDA" => Ok(DayCount::ActActIsda),
+        "ACT/360" => Ok(DayCount::Act360),
+        "ACT/365" | "ACT/365F" => Ok(DayCount::Act365F),
+        "30/360" | "30/360US" => Ok(DayCount::Thirty360Us),
+        "30E/360" => Ok(DayCount::Thirty360E),
+        "30E/360ISDA" => Ok(DayCount::Thirty360EIsda),
+        "NL/365" => Ok(DayCount::NL365),
+        _ => Err(format!("day_count({}) is undefined", day_count)),
+    }
+}
+
+// The coupon period the accrual belongs to. ACT/ACT ICMA measures the accrual against
+// the (notional) regular periods of `months` length that roll backward from `ref_end`
+// (sticking to month ends if `eom`), and 30E/360 ISDA needs to know the maturity date.
+#[derive(Debug)]
+pub struct AccrualPeriod {
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+    pub ref_end: NaiveDate,
+    pub months: i32,
+    pub eom: bool,
+    pub mty_date: NaiveDate,
+}
+
+fn days(d0: &NaiveDate, d1: &NaiveDate) -> f64 {
+    d1.signed_duration_since(*d0).num_days() as f64
+}
+
+fn is_leap_year(year: i32) -> bool {
+    NaiveDate::from_ymd_opt(year, 2, 29).is_some()
+}
+
+fn is_feb_end(date: &NaiveDate) -> bool {
+    date.month() == 2 && is_month_end(date)
+}
+
+fn thirty360(d0: &NaiveDate, d1: &NaiveDate, day0: u32, day1: u32) -> f64 {
+    let years = (d1.year() - d0.year()) as f64;
+    // must be as f64 first, otherwise u32 - u32 may overflow (when negative)
+    let months = d1.month() as f64 - d0.month() as f64;
+    let days = day1 as f64 - day0 as f64;
+    (360.0 * years + 30.0 * months + days) / 360.0
+}
+
+impl DayCount {
+    // The year fraction between `d0` and `d1`, which should both lie in `period`
+    pub fn year_frac(&self, d0: &NaiveDate, d1: &NaiveDate, period: &AccrualPeriod) -> f64 {
+        if d1 <= d0 {
+            return 0.0;
+        }
+        match self {
+            DayCount::ActActIcma => Self::act_act_icma(d0, d1, period),
+            DayCount::ActActIsda => {
+                let mut frac = 0.0;
+                for year in d0.year()..=d1.year() {
+                    let start = NaiveDate::from_ymd_opt(year, 1, 1).unwrap().max(*d0);
+                    let end = NaiveDate::from_ymd_opt(year + 1, 1, 1).unwrap().min(*d1);
+                    let basis = if is_leap_year(year) { 366.0 } else { 365.0 };
+                    frac += days(&start, &end) / basis;
+                }
+                frac
+            }
+            DayCount::Act360 => days(d0, d1) / 360.0,
+            DayCount::Act365F => days(d0, d1) / 365.0,
+            DayCount::NL365 => {
+                let leap_days = (d0.year()..=d1.year())
+                    .filter_map(|year| NaiveDate::from_ymd_opt(year, 2, 29))
+                    .filter(|date| date > d0 && date <= d1)
+                    .count();
+                (days(d0, d1) - leap_days as f64) / 365.0
+            }
+            DayCount::Thirty360Us => {
+                let mut day0 = d0.day();
+                let mut day1 = d1.day();
+                if is_feb_end(d0) && is_feb_end(d1) {
+                    day1 = 30;
+                }
+                if is_feb_end(d0) {
+                    day0 = 30;
+                }
+                if day1 == 31 && day0 >= 30 {
+                    day1 = 30;
+                }
+                if day0 == 31 {
+                    day0 = 30;
+                }
+                thirty360(d0, d1, day0, day1)
+            }
+            DayCount::Thirty360E => thirty360(d0, d1, d0.day().min(30), d1.day().min(30)),
+            DayCount::Thirty360EIsda => {
+                let day0 = if is_month_end(d0) { 30 } else { d0.day() };
+                let day1 = if is_month_end(d1) && !(d1 == &period.mty_date && d1.month() == 2) {
+                    30
+                } else {
+                    d1.day()
+                };
+                thirty360(d0, d1, day0, day1)
+            }
+        }
+    }
+    // Each notional period contributes its days divided by (frequency * days of that
+    // notional period). For a regular period, it's simply days / (frequency * period days).
+    fn act_act_icma(d0: &NaiveDate, d1: &NaiveDate, period: &AccrualPeriod) -> f64 {
+        let freq = 12.0 / period.months as f64;
+        let mut frac = 0.0;
+        let mut k = 0;
+        loop {
+            let nominal_end =
+                date_handle::roll_months(&period.ref_end, -period.months * k, period.eom);
+            let nominal_start =
+                date_handle::roll_months(&period.ref_end, -period.months * (k + 1), period.eom);
+            let lower = nominal_start.max(period.start).max(*d0);
+            let upper = nominal_end.min(*d1);
+            if upper > lower {
+                frac += days(&lower, &upper) / (freq * days(&nominal_start, &nominal_end));
+            }
+            if nominal_start <= period.start || nominal_start <= *d0 {
+                break;
+            }
+            k += 1;
+        }
+        frac
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assert::NearEq;
+    fn from_ymd(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).expect("invalid or out-of-range date")
+    }
+    fn period(start: NaiveDate, end: NaiveDate, months: i32) -> AccrualPeriod {
+        AccrualPeriod {
+            start,
+            end,
+            ref_end: end,
+            months,
+            eom: false,
+            mty_date: end,
+        }
+    }
+    #[test]
+    fn parse_day_count() {
+        assert_eq!(to_day_count("ACT/ACT").unwrap(), DayCount::ActActIcma);
+        assert_eq!(to_day_count("act/act isda").unwrap(), DayCount::ActActIsda);
+        assert_eq!(
+            to_day_count("30E/360 ISDA").unwrap(),
+            DayCount::Thirty360EIsda
+        );
+        assert!(to_day_count("ACT/999").is_err());
+    }
+    #[test]
+    fn act_based() {
+        let d0 = from_ymd(2019, 12, 1);
+        let d1 = from_ymd(2020, 3, 1);
+        let p = period(d0, d1, 3);
+        assert_near_eq!(DayCount::Act360.year_frac(&d0, &d1, &p), 91.0 / 360.0);
+        assert_near_eq!(DayCount::Act365F.year_frac(&d0, &d1, &p), 91.0 / 365.0);
+        assert_near_eq!(DayCount::NL365.year_frac(&d0, &d1, &p), 90.0 / 365.0);
+        assert_near_eq!(
+            DayCount::ActActIsda.year_frac(&d0, &d1, &p),
+            31.0 / 365.0 + 60.0 / 366.0
+        );
+        assert_near_eq!(DayCount::ActActIcma.year_frac(&d0, &d1, &p), 0.25);
+        let mid = from_ymd(2020, 1, 1);
+        assert_near_eq!(
+            DayCount::ActActIcma.year_frac(&d0, &mid, &p),
+            31.0 / (4.0 * 91.0)
+        );
+    }
+    #[test]
+    fn icma_long_period() {
+        // a long period covering two notional semi-annual periods
+        let d0 = from_ymd(2020, 1, 1);
+        let d1 = from_ymd(2021, 1, 1);
+        let p = period(d0, d1, 6);
+        assert_near_eq!(DayCount::ActActIcma.year_frac(&d0, &d1, &p), 1.0);
+        let p = period(from_ymd(2020, 3, 1), d1, 6);
+        let ref_date = from_ymd(2020, 8, 1);
+        assert_near_eq!(
+            DayCount::ActActIcma.year_frac(&p.start, &ref_date, &p),
+            (from_ymd(2020, 7, 1) - p.start).num_days() as f64 / (2.0 * 182.0)
+                + 31.0 / (2.0 * 184.0)
+        );
+    }
+    #[test]
+    fn thirty_based() {
+        let d0 = from_ymd(2020, 2, 29);
+        let d1 = from_ymd(2020, 8, 31);
+        let p = period(d0, d1, 6);
+        assert_near_eq!(DayCount::Thirty360Us.year_frac(&d0, &d1, &p), 0.5);
+        assert_near_eq!(
+            DayCount::Thirty360E.year_frac(&d0, &d1, &p),
+            (6.0 * 30.0 + 1.0) / 360.0
+        );
+        assert_near_eq!(DayCount::Thirty360EIsda.year_frac(&d0, &d1, &p), 0.5);
+        // the maturity date in February is not extended to the 30th
+        let d0 = from_ymd(2020, 8, 31);
+        let d1 = from_ymd(2021, 2, 28);
+        let p = period(d0, d1, 6);
+        assert_near_eq!(
+            DayCount::Thirty360EIsda.year_frac(&d0, &d1, &p),
+            (5.0 * 30.0 + 28.0) / 360.0
+        );
+    }
+}
@@ -33,6 +33,18 @@ where
     }
 }
 
+// The root within [lower, upper], which starts from Newton's method and falls back to the
+// bisection. Unlike `find_root()`, the root may have a different sign from the guess `x`.
+pub fn find_root_in<F>(x: f64, lower: f64, upper: f64, func: F) -> Option<f64>
+where
+    F: Fn(f64) -> f64,
+{
+    match newton(x, &func) {
+        Some(root) if root >= lower && root <= upper => Some(root),
+        _ => bisection(Bounds::new_from_range(lower, upper), &func),
+    }
+}
+
 fn newton<F>(x: f64, f: F) -> Option<f64>
 where
     F: Fn(f64) -> f64,
@@ -0,0 +1,106 @@
+use crate::curve::ZeroCurve;
+use crate::date_handle;
+use chrono::NaiveDate;
+use std::collections::BTreeMap;
+use std::rc::Rc;
+
+// The reference index of the floating coupons, e.g., SHIBOR 3M, LPR 1Y or SOFR. The rates
+// fixed on or before the last date of `fixings` are known, while the later ones are projected
+// off the forward curve (a zero curve as of the date), or the last fixing if no curve. The
+// rates fixed before the first date of `fixings` take the earliest fixing.
+#[derive(Debug, Default)]
+pub struct RateIndex {
+    fixings: BTreeMap<NaiveDate, f64>,
+    fwd_curve: Option<(NaiveDate, ZeroCurve)>,
+}
+
+impl RateIndex {
+    pub fn new(
+        fixings: BTreeMap<NaiveDate, f64>,
+        fwd_curve: Option<(NaiveDate, ZeroCurve)>,
+    ) -> Result<Self, String> {
+        if fixings.is_empty() && fwd_curve.is_none() {
+            return Err("either fixings or fwd_curve must be provided".to_string());
+        }
+        Ok(Self { fixings, fwd_curve })
+    }
+    // The index rate for the period from `start` to `end`, fixed on `fixing_date`
+    pub fn rate(&self, fixing_date: &NaiveDate, start: &NaiveDate, end: &NaiveDate) -> f64 {
+        let known = self
+            .fixings
+            .keys()
+            .next_back()
+            .map_or(false, |last| fixing_date <= last);
+        let fixing = self.fixings.range(..=*fixing_date).next_back();
+        match (known, fixing, &self.fwd_curve) {
+            (true, Some((_, rate)), _) => *rate,
+            (true, None, _) => *self.fixings.values().next().unwrap(),
+            (_, _, Some((curve_date, curve))) => {
+                let t0 = date_handle::year_frac(start, curve_date).max(0.0);
+                let t1 = date_handle::year_frac(end, curve_date).max(0.0);
+                if t1 > t0 {
+                    (curve.df(t0) / curve.df(t1) - 1.0) / (t1 - t0)
+                } else {
+                    curve.zero(t0)
+                }
+            }
+            // the last fixing is the best guess without a forward curve
+            _ => *self.fixings.values().next_back().unwrap(),
+        }
+    }
+}
+
+// The floating coupon rate, which is the index rate plus `spread`. The index is reset every
+// `reset_months` (or once per coupon period if `None`), and the rate is fixed `lookback`
+// business days before each reset date.
+#[derive(Debug, Clone)]
+pub struct FloatCpn {
+    pub index: Rc<RateIndex>,
+    pub spread: f64,
+    pub reset_months: Option<i32>,
+    pub lookback: i64,
+}
+
+// The index resets `reset_freq` times a year, or once per coupon period if NA
+pub fn to_reset_months(reset_freq: i32) -> Result<Option<i32>, String> {
+    match reset_freq {
+        i32::MIN => Ok(None),
+        1 | 2 | 4 | 6 | 12 => Ok(Some(12 / reset_freq)),
+        _ => Err(format!("reset_freq({}) is undefined", reset_freq)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assert::NearEq;
+    fn from_ymd(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).expect("invalid or out-of-range date")
+    }
+    #[test]
+    fn index_rate() {
+        let mut fixings: BTreeMap<NaiveDate, f64> = BTreeMap::new();
+        fixings.insert(from_ymd(2021, 1, 4), 0.02);
+        fixings.insert(from_ymd(2021, 1, 5), 0.021);
+        let start = from_ymd(2021, 1, 5);
+        let end = from_ymd(2021, 4, 5);
+        let index = RateIndex::new(fixings.clone(), None).unwrap();
+        assert_eq!(index.rate(&from_ymd(2021, 1, 4), &start, &end), 0.02);
+        // the earliest fixing before the first fixing date
+        assert_eq!(index.rate(&from_ymd(2020, 12, 31), &start, &end), 0.02);
+        // the latest fixing before a holiday
+        assert_eq!(index.rate(&from_ymd(2021, 1, 5), &start, &end), 0.021);
+        assert_eq!(index.rate(&from_ymd(2021, 7, 5), &start, &end), 0.021);
+        let curve = ZeroCurve::new(vec![1.0], vec![0.03]).unwrap();
+        let index = RateIndex::new(fixings, Some((from_ymd(2021, 1, 5), curve))).unwrap();
+        assert_eq!(index.rate(&from_ymd(2021, 1, 5), &start, &end), 0.021);
+        assert_eq!(index.rate(&from_ymd(2020, 12, 31), &start, &end), 0.02);
+        let start = from_ymd(2021, 7, 5);
+        let end = from_ymd(2022, 1, 5);
+        assert_near_eq!(
+            index.rate(&start, &start, &end),
+            ((0.03f64 * 0.5).exp() - 1.0) / 0.5
+        );
+        assert!(RateIndex::new(BTreeMap::new(), None).is_err());
+    }
+}
@@ -0,0 +1,380 @@
+use crate::bond::{BondCfType, Convention, FixedBond};
+use chrono::{Datelike, NaiveDate};
+
+// The notional coupon rates of the treasury futures
+const CFFEX_NOTIONAL_CPN: f64 = 0.03;
+const CBOT_NOTIONAL_CPN: f64 = 0.06;
+
+// The exchange of the treasury futures, which sets the conversion factor formula, the day count
+// of the repo rate and the yield convention of the deliverable bonds
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Exchange {
+    Cffex,
+    Cbot,
+}
+
+impl Exchange {
+    // The days of a year that the repo rate accrues over
+    fn repo_basis(&self) -> f64 {
+        match self {
+            Exchange::Cffex => 365.0,
+            Exchange::Cbot => 360.0,
+        }
+    }
+    // The yield convention of the deliverable bonds, i.e., the CCDC valuation of the China
+    // government bonds and the street convention of the US Treasuries
+    fn convention(&self) -> Convention {
+        match self {
+            Exchange::Cffex => Convention::ChinaInterbank,
+            Exchange::Cbot => Convention::Street,
+        }
+    }
+}
+
+// The treasury futures contract
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Contract {
+    // the 2Y (TS), 5Y (TF), 10Y (T) and 30Y (TL) treasury futures of CFFEX
+    Ts,
+    Tf,
+    T,
+    Tl,
+    // the 2Y (ZT), 3Y (Z3N), 5Y (ZF), 10Y (ZN), Ultra 10Y (TN), Bond (ZB) and Ultra Bond (UB)
+    // treasury futures of CBOT
+    Zt,
+    Z3n,
+    Zf,
+    Zn,
+    Tn,
+    Zb,
+    Ub,
+}
+
+pub fn to_contract(contract: &str) -> Result<Contract, String> {
+    match contract.to_uppercase().as_str() {
+        "TS" => Ok(Contract::Ts),
+        "TF" => Ok(Contract::Tf),
+        "T" => Ok(Contract::T),
+        "TL" => Ok(Contract::Tl),
+        "ZT" => Ok(Contract::Zt),
+        "Z3N" => Ok(Contract::Z3n),
+        "ZF" => Ok(Contract::Zf),
+        "ZN" => Ok(Contract::Zn),
+        "TN" => Ok(Contract::Tn),
+        "ZB" => Ok(Contract::Zb),
+        "UB" => Ok(Contract::Ub),
+        _ => Err(format!("contract({}) is undefined", contract)),
+    }
+}
+
+impl Contract {
+    pub fn exchange(&self) -> Exchange {
+        match self {
+            Contract::Ts | Contract::Tf | Contract::T | Contract::Tl => Exchange::Cffex,
+            _ => Exchange::Cbot,
+        }
+    }
+    // The months that CBOT rounds the remaining term of the deliverable bonds down to, i.e.,
+    // whole months for the 2Y, 3Y and 5Y notes and quarters for the others
+    fn rounding_months(&self) -> i32 {
+        match self {
+            Contract::Zt | Contract::Z3n | Contract::Zf => 1,
+            _ => 3,
+        }
+    }
+}
+
+// The whole months from `d0` to `d1`
+fn months_between(d0: &NaiveDate, d1: &NaiveDate) -> i32 {
+    let months = (d1.year() - d0.year()) * 12 + d1.month() as i32 - d0.month() as i32;
+    if d1.day() < d0.day() {
+        months - 1
+    } else {
+        months
+    }
+}
+
+fn round4(x: f64) -> f64 {
+    (x * 1e4).round() / 1e4
+}
+
+// The conversion factor of the deliverable `bond` for the futures delivered in the month of
+// `delivery_date`, i.e., the price per 1 face value at the notional coupon rate as the yield,
+// rounded to 4 decimals. It's `None` for the zero coupon bonds.
+pub fn conversion_factor(
+    bond: &FixedBond,
+    delivery_date: &NaiveDate,
+    contract: Contract,
+) -> Option<f64> {
+    let freq = bond.cpn_freq();
+    if freq == 0 {
+        return None;
+    }
+    let month_start = delivery_date.with_day(1)?;
+    let cpn = bond.cpn_rate();
+    let cf = match contract.exchange() {
+        Exchange::Cffex => {
+            // the remaining coupons after the delivery month starts, where x is the months from
+            // the delivery month to the next coupon
+            let cpn_dates: Vec<NaiveDate> = bond
+                .pay_schedule()
+                .into_iter()
+                .map(|(accrual_date, _)| accrual_date)
+                .filter(|date| date > &month_start)
+                .collect();
+            let n = cpn_dates.len() as i32;
+            let x = months_between(&month_start, &cpn_dates.first()?.with_day(1)?) as f64;
+            let (r, f) = (CFFEX_NOTIONAL_CPN, freq as f64);
+            let v = 1.0 / (1.0 + r / f);
+            v.powf(x * f / 12.0) * (cpn / f + cpn / r + (1.0 - cpn / r) * v.powi(n - 1))
+                - cpn / f * (1.0 - x * f / 12.0)
+        }
+        Exchange::Cbot => {
+            // the remaining life in whole years and the months rounded down by the contract
+            let months = months_between(&month_start, &bond.mty_date());
+            if months <= 0 {
+                return None;
+            }
+            let rounding = contract.rounding_months();
+            let (n, z) = (months / 12, months % 12 / rounding * rounding);
+            let r = CBOT_NOTIONAL_CPN / 2.0;
+            let v = if z < 7 { z } else { z - 6 } as f64;
+            let a = (1.0 + r).powf(-v / 6.0);
+            let b = cpn / 2.0 * (6.0 - v) / 6.0;
+            let c = if z < 7 {
+                (1.0 + r).powi(-2 * n)
+            } else {
+                (1.0 + r).powi(-2 * n - 1)
+            };
+            let d = cpn / CBOT_NOTIONAL_CPN * (1.0 - c);
+            a * (cpn / 2.0 + c + d) - b
+        }
+    };
+    Some(round4(cf))
+}
+
+// The basis of the deliverable bond against the futures, in price points
+#[derive(Debug)]
+pub struct FuturesBasis {
+    pub cf: f64,
+    pub gross_basis: f64,
+    pub carry: f64,
+    pub net_basis: f64,
+    pub irr: f64,
+    pub dv01: Option<f64>,
+}
+
+// The basis analytics of the deliverable `bond` bought on `ref_date` at `clean_price` and
+// delivered into the futures at `futures_price` on `delivery_date`. The carry is the coupon
+// income net of the cost of funding the dirty price at the simple `repo_rate` until the
+// delivery, and the net basis is the gross basis less the carry. The implied repo rate (IRR) is
+// the simple return of buying the bond and delivering it, with the interim coupons reinvested
+// at the IRR as well. The DV01 is the futures-equivalent one, i.e., the bond's DV01 under the
+// yield convention of the exchange divided by the conversion factor, which is `None` if the
+// yield can't be solved.
+pub fn basis(
+    bond: &FixedBond,
+    ref_date: &NaiveDate,
+    clean_price: f64,
+    delivery_date: &NaiveDate,
+    futures_price: f64,
+    repo_rate: f64,
+    contract: Contract,
+) -> Option<FuturesBasis> {
+    if delivery_date <= ref_date {
+        return None;
+    }
+    let cf = conversion_factor(bond, delivery_date, contract)?;
+    let exchange = contract.exchange();
+    let dirty_price = bond.dirty_price(ref_date, clean_price);
+    let accrued = bond.accrued(ref_date, true);
+    let delivery_accrued = bond.accrued(delivery_date, true);
+    let basis = exchange.repo_basis();
+    let years = |from: &NaiveDate| (*delivery_date - *from).num_days() as f64 / basis;
+    let cpns = bond.received(BondCfType::Coupon, ref_date, delivery_date);
+    let cpn: f64 = cpns.iter().map(|(_, value)| value).sum();
+    let carry = cpn + delivery_accrued - accrued - dirty_price * repo_rate * years(ref_date);
+    let gross_basis = clean_price - futures_price * cf;
+    let invoice = futures_price * cf + delivery_accrued;
+    let cpn_time: f64 = cpns.iter().map(|(date, value)| value * years(date)).sum();
+    let irr = (invoice + cpn - dirty_price) / (dirty_price * years(ref_date) - cpn_time);
+    let dv01 = bond
+        .result(ref_date, clean_price, exchange.convention())
+        .map(|v| v.dv01 / cf);
+    Some(FuturesBasis {
+        cf,
+        gross_basis,
+        carry,
+        net_basis: gross_basis - carry,
+        irr,
+        dv01,
+    })
+}
+
+// The index of the cheapest-to-deliver bond, i.e., the one with the highest implied repo rate
+pub fn cheapest(irrs: &[Option<f64>]) -> Option<usize> {
+    irrs.iter()
+        .enumerate()
+        .filter_map(|(i, irr)| irr.map(|irr| (i, irr)))
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(i, _)| i)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assert::NearEq;
+    fn from_ymd(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).expect("invalid or out-of-range date")
+    }
+    #[test]
+    fn conversion_factors() {
+        let delivery_date = from_ymd(2021, 6, 11);
+        // the bond paying the notional coupon on the delivery month is priced at par
+        let bond =
+            FixedBond::new(from_ymd(2020, 6, 1), from_ymd(2030, 6, 1), 100.0, 0.03, 1).unwrap();
+        assert_near_eq!(
+            conversion_factor(&bond, &delivery_date, Contract::T).unwrap(),
+            1.0
+        );
+        let bond =
+            FixedBond::new(from_ymd(2020, 6, 1), from_ymd(2030, 6, 1), 100.0, 0.06, 2).unwrap();
+        assert_near_eq!(
+            conversion_factor(&bond, &delivery_date, Contract::Zn).unwrap(),
+            1.0
+        );
+        // 6 coupons left, the next of which is 3 months after the delivery month
+        let bond = FixedBond::new(
+            from_ymd(2019, 9, 15),
+            from_ymd(2026, 9, 15),
+            100.0,
+            0.035,
+            1,
+        )
+        .unwrap();
+        let v: f64 = 1.0 / 1.03;
+        let expect =
+            v.powf(0.25) * (0.035 + 0.035 / 0.03 + (1.0 - 0.035 / 0.03) * v.powi(5)) - 0.035 * 0.75;
+        assert_near_eq!(
+            conversion_factor(&bond, &delivery_date, Contract::T).unwrap(),
+            round4(expect)
+        );
+        // 5 years and 5 months left, rounded down to 3 months by ZN but not by ZF
+        let bond = FixedBond::new(
+            from_ymd(2016, 11, 15),
+            from_ymd(2026, 11, 15),
+            100.0,
+            0.02,
+            2,
+        )
+        .unwrap();
+        let c = 1.03f64.powi(-10);
+        let a = 1.03f64.powf(-0.5);
+        let expect = a * (0.01 + c + 0.02 / 0.06 * (1.0 - c)) - 0.01 * 0.5;
+        assert_near_eq!(
+            conversion_factor(&bond, &delivery_date, Contract::Zn).unwrap(),
+            round4(expect)
+        );
+        let a = 1.03f64.powf(-5.0 / 6.0);
+        let expect = a * (0.01 + c + 0.02 / 0.06 * (1.0 - c)) - 0.01 / 6.0;
+        assert_near_eq!(
+            conversion_factor(&bond, &delivery_date, Contract::Zf).unwrap(),
+            round4(expect)
+        );
+        // the 0.375% note of 2025-11-30 into the June 2021 5Y (ZF) futures, with 4 years and 5
+        // months left
+        let bond = FixedBond::new(
+            from_ymd(2020, 11, 30),
+            from_ymd(2025, 11, 30),
+            100.0,
+            0.00375,
+            2,
+        )
+        .unwrap();
+        assert_near_eq!(
+            conversion_factor(&bond, &delivery_date, Contract::Zf).unwrap(),
+            0.7846
+        );
+        // the examples of Hull (Options, Futures, and Other Derivatives): the 10% bond with 20
+        // years and 2 months left and the 8% bond with 18 years and 4 months left
+        let bond =
+            FixedBond::new(from_ymd(2020, 2, 1), from_ymd(2041, 8, 1), 100.0, 0.1, 2).unwrap();
+        assert_near_eq!(
+            conversion_factor(&bond, &delivery_date, Contract::Zb).unwrap(),
+            1.4623
+        );
+        let bond =
+            FixedBond::new(from_ymd(2019, 10, 1), from_ymd(2039, 10, 1), 100.0, 0.08, 2).unwrap();
+        assert_near_eq!(
+            conversion_factor(&bond, &delivery_date, Contract::Zb).unwrap(),
+            1.2199
+        );
+        let zero =
+            FixedBond::new(from_ymd(2020, 6, 1), from_ymd(2030, 6, 1), 100.0, 0.0, 0).unwrap();
+        assert!(conversion_factor(&zero, &delivery_date, Contract::T).is_none());
+        assert_eq!(to_contract("tf").unwrap(), Contract::Tf);
+        assert_eq!(to_contract("ZF").unwrap().exchange(), Exchange::Cbot);
+        assert!(to_contract("TY").is_err());
+    }
+    #[test]
+    fn implied_repo() {
+        let ref_date = from_ymd(2021, 3, 1);
+        let delivery_date = from_ymd(2021, 6, 11);
+        let days = (delivery_date - ref_date).num_days() as f64;
+        let bond =
+            FixedBond::new(from_ymd(2020, 6, 1), from_ymd(2030, 6, 1), 100.0, 0.03, 1).unwrap();
+        let res = basis(
+            &bond,
+            &ref_date,
+            99.0,
+            &delivery_date,
+            98.0,
+            0.02,
+            Contract::T,
+        )
+        .unwrap();
+        assert_near_eq!(res.gross_basis, 1.0);
+        // the coupon paid on 2021-06-01 before the delivery
+        let dirty_price = bond.dirty_price(&ref_date, 99.0);
+        let (accrued, delivery_accrued) = (dirty_price - 99.0, bond.accrued(&delivery_date, true));
+        let carry = 3.0 + delivery_accrued - accrued - dirty_price * 0.02 * days / 365.0;
+        assert_near_eq!(res.carry, carry);
+        assert_near_eq!(res.net_basis, 1.0 - carry);
+        // the implied repo rate funds the bond to the invoice price plus the reinvested coupon
+        let cpn_years = (delivery_date - from_ymd(2021, 6, 1)).num_days() as f64 / 365.0;
+        let value = (98.0 + delivery_accrued) + 3.0 * (1.0 + res.irr * cpn_years);
+        assert_near_eq!(value, dirty_price * (1.0 + res.irr * days / 365.0));
+        // funding at the implied repo rate leaves no net basis
+        let at_irr = basis(
+            &bond,
+            &ref_date,
+            99.0,
+            &delivery_date,
+            98.0,
+            res.irr,
+            Contract::T,
+        )
+        .unwrap();
+        assert!(at_irr.net_basis.abs() < 1e-2);
+        let dv01 = bond
+            .result(&ref_date, 99.0, Convention::ChinaInterbank)
+            .unwrap()
+            .dv01;
+        assert_near_eq!(res.dv01.unwrap(), dv01);
+        // the basis is kept even if the yield can't be solved
+        let res = basis(
+            &bond,
+            &ref_date,
+            -10.0,
+            &delivery_date,
+            98.0,
+            0.02,
+            Contract::T,
+        )
+        .unwrap();
+        assert_near_eq!(res.gross_basis, -108.0);
+        assert!(res.dv01.is_none());
+        assert_eq!(cheapest(&[Some(0.01), None, Some(0.02)]), Some(2));
+        assert_eq!(cheapest(&[None]), None);
+    }
+}
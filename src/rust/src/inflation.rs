@@ -0,0 +1,86 @@
+use crate::date_handle;
+use chrono::{Datelike, NaiveDate};
+use std::collections::BTreeMap;
+use std::rc::Rc;
+
+// The monthly CPI series keyed by the first day of each month. The months after the last
+// one are projected flat, i.e., no more inflation is assumed.
+#[derive(Debug)]
+pub struct CpiIndex {
+    cpi: BTreeMap<NaiveDate, f64>,
+}
+
+fn month_start(date: &NaiveDate) -> NaiveDate {
+    date.with_day(1).unwrap()
+}
+
+impl CpiIndex {
+    pub fn new(cpi: BTreeMap<NaiveDate, f64>) -> Result<Self, String> {
+        if cpi.is_empty() {
+            return Err("cpi must not be empty".to_string());
+        }
+        if cpi.values().any(|v| !v.is_finite() || *v <= 0.0) {
+            return Err("cpi must be positive".to_string());
+        }
+        let cpi = cpi
+            .into_iter()
+            .map(|(date, value)| (month_start(&date), value))
+            .collect();
+        Ok(Self { cpi })
+    }
+    fn monthly(&self, month: &NaiveDate) -> f64 {
+        match self.cpi.range(..=*month).next_back() {
+            Some((_, value)) => *value,
+            None => *self.cpi.values().next().unwrap(),
+        }
+    }
+    // The reference CPI on `date`, i.e., the CPI of `lag` months earlier. If `interp`, it's
+    // linearly interpolated by the day of the month towards the CPI of the next month, e.g.,
+    // the reference CPI of TIPS on Apr 16 is CPI(Jan) + 15 / 30 * (CPI(Feb) - CPI(Jan)).
+    pub fn ref_cpi(&self, date: &NaiveDate, lag: i32, interp: bool) -> f64 {
+        let month = month_start(&date_handle::add_months(date, -lag));
+        let cpi0 = self.monthly(&month);
+        if !interp {
+            return cpi0;
+        }
+        let cpi1 = self.monthly(&date_handle::add_months(&month, 1));
+        let days = (date_handle::add_months(&month_start(date), 1) - month_start(date)).num_days();
+        cpi0 + (date.day() - 1) as f64 / days as f64 * (cpi1 - cpi0)
+    }
+}
+
+// The inflation linkage of a bond, whose principal and coupons are scaled by the index ratio,
+// i.e., the reference CPI over `base_cpi` (the reference CPI on the value date if `None`).
+// If `floor`, the principal repaid is no less than the par.
+#[derive(Debug, Clone)]
+pub struct Linker {
+    pub cpi: Rc<CpiIndex>,
+    pub base_cpi: Option<f64>,
+    pub lag: i32,
+    pub interp: bool,
+    pub floor: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assert::NearEq;
+    fn from_ymd(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).expect("invalid or out-of-range date")
+    }
+    #[test]
+    fn ref_cpi() {
+        let mut cpi: BTreeMap<NaiveDate, f64> = BTreeMap::new();
+        cpi.insert(from_ymd(2021, 1, 15), 100.0);
+        cpi.insert(from_ymd(2021, 2, 1), 103.0);
+        let cpi = CpiIndex::new(cpi).unwrap();
+        let date = from_ymd(2021, 4, 16);
+        assert_near_eq!(cpi.ref_cpi(&date, 3, true), 100.0 + 15.0 / 30.0 * 3.0);
+        assert_near_eq!(cpi.ref_cpi(&date, 3, false), 100.0);
+        assert_near_eq!(cpi.ref_cpi(&from_ymd(2021, 5, 1), 3, true), 103.0);
+        // projected flat after the last month, and backward before the first
+        assert_near_eq!(cpi.ref_cpi(&from_ymd(2022, 5, 1), 3, true), 103.0);
+        assert_near_eq!(cpi.ref_cpi(&from_ymd(2020, 5, 1), 3, true), 100.0);
+        assert!(CpiIndex::new(BTreeMap::new()).is_err());
+    }
+}
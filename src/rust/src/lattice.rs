@@ -0,0 +1,201 @@
+use crate::curve::ZeroCurve;
+
+// The time steps per year of the trinomial tree
+const STEPS_PER_YEAR: f64 = 50.0;
+
+// The Hull-White one factor short rate model, dr = (theta(t) - a * r) dt + sigma * dW, where
+// `mean_rev` is a and `vol` is sigma. theta(t) is fitted to the discount curve by the tree.
+#[derive(Debug, Copy, Clone)]
+pub struct HullWhite {
+    mean_rev: f64,
+    vol: f64,
+}
+
+// The Hull-White trinomial tree fitted to a discount curve. The short rate at node j of
+// step m is `alphas[m] + j * dx`, and the branching is truncated at `jmax`.
+#[derive(Debug)]
+pub struct Tree {
+    dt: f64,
+    dx: f64,
+    jmax: i64,
+    alphas: Vec<f64>,
+    // the middle node of the branching and the up, middle and down probabilities
+    branches: Vec<(i64, [f64; 3])>,
+}
+
+impl HullWhite {
+    pub fn new(mean_rev: f64, vol: f64) -> Result<Self, String> {
+        if !(mean_rev >= 0.0 && mean_rev.is_finite()) {
+            return Err(format!("mean_rev({}) must be non-negative", mean_rev));
+        }
+        if !(vol >= 0.0 && vol.is_finite()) {
+            return Err(format!("vol({}) must be non-negative", vol));
+        }
+        Ok(Self { mean_rev, vol })
+    }
+    // Build the tree up to `horizon` years, fitted to `curve` by forward induction
+    pub fn tree(&self, curve: &ZeroCurve, horizon: f64) -> Tree {
+        let n = ((horizon * STEPS_PER_YEAR).ceil() as usize).max(1);
+        let dt = horizon.max(0.0) / n as f64;
+        let dx = self.vol * (3.0 * dt).sqrt();
+        let m = (-self.mean_rev * dt).exp() - 1.0;
+        let jmax = if m < 0.0 {
+            ((-0.184 / m).ceil() as i64).min(n as i64)
+        } else {
+            n as i64
+        };
+        let branches: Vec<(i64, [f64; 3])> = (-jmax..=jmax)
+            .map(|j| {
+                // the branching at the edges turns inward
+                let k = j.clamp(1 - jmax, jmax - 1);
+                // the expected move from node j, measured from node k in dx
+                let y = j as f64 * (1.0 + m) - k as f64;
+                let var = 1.0 / 3.0;
+                let pu = (var + y * y + y) / 2.0;
+                let pd = (var + y * y - y) / 2.0;
+                (k, [pu, 1.0 - pu - pd, pd])
+            })
+            .collect();
+        let mut tree = Tree {
+            dt,
+            dx,
+            jmax,
+            alphas: Vec::with_capacity(n),
+            branches,
+        };
+        let width = (2 * jmax + 1) as usize;
+        let mut q = vec![0.0; width];
+        q[jmax as usize] = 1.0;
+        for step in 0..n {
+            let jm = tree.width(step);
+            let sum: f64 = (-jm..=jm)
+                .map(|j| q[(j + jmax) as usize] * (-(j as f64) * dx * dt).exp())
+                .sum();
+            let alpha = (sum / curve.df((step + 1) as f64 * dt)).ln() / dt;
+            tree.alphas.push(alpha);
+            let mut nxt = vec![0.0; width];
+            for j in -jm..=jm {
+                let (k, p) = tree.branches[(j + jmax) as usize];
+                let value = q[(j + jmax) as usize] * (-(alpha + j as f64 * dx) * dt).exp();
+                for (i, p) in p.iter().enumerate() {
+                    nxt[(k + 1 - i as i64 + jmax) as usize] += value * p;
+                }
+            }
+            q = nxt;
+        }
+        tree
+    }
+}
+
+impl Tree {
+    fn steps(&self) -> usize {
+        self.alphas.len()
+    }
+    fn width(&self, step: usize) -> i64 {
+        (step as i64).min(self.jmax)
+    }
+    fn step_of(&self, t: f64) -> usize {
+        if self.dt > 0.0 {
+            ((t / self.dt).round() as usize).min(self.steps())
+        } else {
+            self.steps()
+        }
+    }
+    // The value at time 0 of the cashflows `cfs` (time in years, amount), where the holder
+    // gets no more than the call strike on each `calls` time and no less than the put strike on
+    // each `puts` time. All the rates are shifted by `spread` when discounting.
+    pub fn value(
+        &self,
+        cfs: &[(f64, f64)],
+        calls: &[(f64, f64)],
+        puts: &[(f64, f64)],
+        spread: f64,
+    ) -> f64 {
+        let n = self.steps();
+        let mut cf_at = vec![0.0; n + 1];
+        for (t, cf) in cfs {
+            cf_at[self.step_of(*t)] += cf;
+        }
+        let mut call_at: Vec<Option<f64>> = vec![None; n + 1];
+        for (t, strike) in calls {
+            let step = self.step_of(*t);
+            call_at[step] = Some(call_at[step].map_or(*strike, |v: f64| v.min(*strike)));
+        }
+        let mut put_at: Vec<Option<f64>> = vec![None; n + 1];
+        for (t, strike) in puts {
+            let step = self.step_of(*t);
+            put_at[step] = Some(put_at[step].map_or(*strike, |v: f64| v.max(*strike)));
+        }
+        let exercise = |step: usize, value: f64| {
+            let value = call_at[step].map_or(value, |strike| value.min(strike));
+            put_at[step].map_or(value, |strike| value.max(strike))
+        };
+        let width = (2 * self.jmax + 1) as usize;
+        let mut values = vec![cf_at[n]; width];
+        for step in (0..n).rev() {
+            let jm = self.width(step);
+            let mut prv = vec![0.0; width];
+            for j in -jm..=jm {
+                let (k, p) = self.branches[(j + self.jmax) as usize];
+                let expected: f64 = p
+                    .iter()
+                    .enumerate()
+                    .map(|(i, p)| p * values[(k + 1 - i as i64 + self.jmax) as usize])
+                    .sum();
+                let rate = self.alphas[step] + j as f64 * self.dx + spread;
+                let value = expected * (-rate * self.dt).exp();
+                let value = if step > 0 {
+                    exercise(step, value) + cf_at[step]
+                } else {
+                    value
+                };
+                prv[(j + self.jmax) as usize] = value;
+            }
+            values = prv;
+        }
+        values[self.jmax as usize]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assert::NearEq;
+    #[test]
+    fn fit_to_curve() {
+        let curve = ZeroCurve::new(vec![1.0, 5.0], vec![0.02, 0.04]).unwrap();
+        let model = HullWhite::new(0.1, 0.01).unwrap();
+        let tree = model.tree(&curve, 5.0);
+        for t in [1.0, 2.5, 5.0] {
+            assert_near_eq!(
+                round(tree.value(&[(t, 1.0)], &[], &[], 0.0)),
+                round(curve.df(t))
+            );
+        }
+        // the spread shifts the discount rates in parallel
+        let value = tree.value(&[(5.0, 1.0)], &[], &[], 0.01);
+        assert_near_eq!(round(value), round(curve.df(5.0) * (-0.05f64).exp()));
+    }
+    #[test]
+    fn embedded_options() {
+        let curve = ZeroCurve::new(vec![1.0], vec![0.05]).unwrap();
+        let cfs: Vec<(f64, f64)> = (1..=5)
+            .map(|i| (i as f64, if i == 5 { 105.0 } else { 5.0 }))
+            .collect();
+        let calls = [(2.0, 100.0), (3.0, 100.0)];
+        let puts = [(2.0, 100.0)];
+        let straight = HullWhite::new(0.05, 0.0).unwrap().tree(&curve, 5.0);
+        let model = HullWhite::new(0.05, 0.01).unwrap().tree(&curve, 5.0);
+        let bond = model.value(&cfs, &[], &[], 0.0);
+        assert_near_eq!(round(bond), round(straight.value(&cfs, &[], &[], 0.0)));
+        // the call is worth more with the volatility, while a put adds value
+        let callable = model.value(&cfs, &calls, &[], 0.0);
+        assert!(callable < bond);
+        assert!(callable < straight.value(&cfs, &calls, &[], 0.0));
+        assert!(model.value(&cfs, &[], &puts, 0.0) > bond);
+        assert!(HullWhite::new(-0.1, 0.01).is_err());
+    }
+    fn round(x: f64) -> f64 {
+        (x * 1e8).round() / 1e8
+    }
+}
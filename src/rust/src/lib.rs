@@ -2,14 +2,26 @@ use chrono::NaiveDate;
 use extendr_api::prelude::*;
 mod assert;
 mod bond;
+mod bootstrap;
+mod calendar;
 mod check_len;
+mod curve;
 mod date_handle;
+mod day_count;
 mod find_root;
+mod float_rate;
+mod futures;
+mod inflation;
+mod lattice;
+mod nss;
+mod optimize;
+mod perpetual;
 mod rdate;
 mod rtn;
 mod xirr;
 use rdate::ToRDate;
 use std::collections::BTreeMap;
+use std::rc::Rc;
 
 #[extendr]
 struct RFixedBond {
@@ -18,15 +30,45 @@ struct RFixedBond {
 
 #[extendr]
 impl RFixedBond {
+    #[allow(clippy::too_many_arguments)]
     fn new(
         value_date: Robj,
         mty_date: Robj,
         redem_value: Robj,
         cpn_rate: Robj,
         cpn_freq: Robj,
+        day_count: Robj,
+        stub: Robj,
+        first_cpn_date: Robj,
+        penultimate_cpn_date: Robj,
+        bdc: Robj,
+        adjust_accrual: Robj,
+        eom: Robj,
+        ex_cpn_days: Robj,
+        ex_cpn_bizdays: Robj,
+        settle_days: Robj,
+        settle_bizdays: Robj,
+        holidays: Robj,
     ) -> Self {
         let n = value_date.len();
-        check_len!(value_date, mty_date, redem_value, cpn_rate, cpn_freq);
+        check_len!(
+            value_date,
+            mty_date,
+            redem_value,
+            cpn_rate,
+            cpn_freq,
+            day_count,
+            stub,
+            first_cpn_date,
+            penultimate_cpn_date,
+            bdc,
+            adjust_accrual,
+            eom,
+            ex_cpn_days,
+            ex_cpn_bizdays,
+            settle_days,
+            settle_bizdays
+        );
         let value_date = rdate::robj2date(value_date, "value_date").unwrap();
         let mty_date = rdate::robj2date(mty_date, "mty_date").unwrap();
         let redem_value = redem_value
@@ -35,6 +77,36 @@ impl RFixedBond {
         let cpn_rate = cpn_rate.as_real_slice().expect("cpn_rate must be double");
 
         let cpn_freq = cpn_freq.as_integer_slice().expect("cpn_freq must be int");
+        let day_count = day_count
+            .as_str_vector()
+            .expect("day_count must be character");
+        let stub = stub.as_str_vector().expect("stub must be character");
+        let first_cpn_date = rdate::robj2date(first_cpn_date, "first_cpn_date").unwrap();
+        let penultimate_cpn_date =
+            rdate::robj2date(penultimate_cpn_date, "penultimate_cpn_date").unwrap();
+        let bdc = bdc.as_str_vector().expect("bdc must be character");
+        let adjust_accrual = adjust_accrual
+            .as_logical_slice()
+            .expect("adjust_accrual must be logical");
+        let eom = eom.as_logical_slice().expect("eom must be logical");
+        let ex_cpn_days = ex_cpn_days
+            .as_integer_slice()
+            .expect("ex_cpn_days must be int");
+        let ex_cpn_bizdays = ex_cpn_bizdays
+            .as_logical_slice()
+            .expect("ex_cpn_bizdays must be logical");
+        let settle_days = settle_days
+            .as_integer_slice()
+            .expect("settle_days must be int");
+        let settle_bizdays = settle_bizdays
+            .as_logical_slice()
+            .expect("settle_bizdays must be logical");
+        let holidays: Vec<NaiveDate> = rdate::robj2date(holidays, "holidays")
+            .unwrap()
+            .into_iter()
+            .flatten()
+            .collect();
+        let calendar = Rc::new(calendar::Calendar::new(holidays));
         let mut out: Vec<Option<bond::FixedBond>> = Vec::new();
         for i in 0..n {
             if value_date[i] == None
@@ -42,6 +114,8 @@ impl RFixedBond {
                 || redem_value[i].is_na()
                 || cpn_rate[i].is_na()
                 || cpn_freq[i].is_na()
+                || adjust_accrual[i].is_na()
+                || eom[i].is_na()
             {
                 out.push(None);
             } else {
@@ -51,7 +125,23 @@ impl RFixedBond {
                     redem_value[i],
                     cpn_rate[i],
                     cpn_freq[i],
-                );
+                )
+                .and_then(|bond| {
+                    let bond = bond
+                        .with_day_count(day_count::to_day_count(day_count[i])?)
+                        .with_stub(bond::to_stub(stub[i])?)
+                        .with_eom(eom[i].is_true())
+                        .with_first_cpn_date(first_cpn_date[i])?
+                        .with_penultimate_cpn_date(penultimate_cpn_date[i])?
+                        .with_calendar(
+                            calendar.clone(),
+                            calendar::to_bus_day_conv(bdc[i])?,
+                            adjust_accrual[i].is_true(),
+                        )
+                        .with_ex_cpn(ex_cpn_days[i] as i64, ex_cpn_bizdays[i].is_true())?
+                        .with_settlement(settle_days[i] as i64, settle_bizdays[i].is_true())?;
+                    Ok(bond)
+                });
                 if bond.is_ok() {
                     out.push(Some(bond.unwrap()));
                 } else {
@@ -61,9 +151,261 @@ impl RFixedBond {
         }
         RFixedBond { bonds: out }
     }
-    fn ytm_dur(&self, ref_date: Robj, clean_price: Robj) -> Robj {
-        check_len!(self.bonds, ref_date, clean_price);
+    // Set the long-format schedules of `kind`, i.e., "amort" (the principal repayments), "call",
+    // "put" or "step" (the coupon rate steps), keyed by the 1-based bond index `id`. The bond
+    // becomes NA if its schedule is invalid.
+    fn with_schedule(&mut self, kind: &str, id: Robj, date: Robj, value: Robj) {
+        type With = fn(
+            bond::FixedBond,
+            BTreeMap<NaiveDate, f64>,
+        ) -> std::result::Result<bond::FixedBond, String>;
+        let with: With = match kind {
+            "amort" => bond::FixedBond::with_amortization,
+            "call" => bond::FixedBond::with_calls,
+            "put" => bond::FixedBond::with_puts,
+            "step" => bond::FixedBond::with_cpn_schedule,
+            _ => panic!("schedule kind({}) is undefined", kind),
+        };
+        let mut schedules = to_schedules(id, date, value, kind);
+        for (i, bond) in self.bonds.iter_mut().enumerate() {
+            if let Some(schedule) = schedules.remove(&(i as i32 + 1)) {
+                *bond = bond.take().and_then(|bond| with(bond, schedule).ok());
+            }
+        }
+    }
+    // Make the bonds with a non-NA `index` FRNs, where the indexes are built from the long-format
+    // fixings and the forward curves as of `curve_date`
+    #[allow(clippy::too_many_arguments)]
+    fn with_float(
+        &mut self,
+        index: Robj,
+        spread: Robj,
+        reset_freq: Robj,
+        lookback: Robj,
+        fixing_index: Robj,
+        fixing_date: Robj,
+        fixing_rate: Robj,
+        curve_index: Robj,
+        curve_tenor: Robj,
+        curve_rate: Robj,
+        curve_date: Robj,
+    ) {
+        check_len!(self.bonds, index, spread, reset_freq, lookback);
+        let index = index.as_str_vector().expect("index must be character");
+        let spread = spread.as_real_slice().expect("spread must be double");
+        let reset_freq = reset_freq
+            .as_integer_slice()
+            .expect("reset_freq must be int");
+        let lookback = lookback.as_integer_slice().expect("lookback must be int");
+        let rate_indexes = to_rate_indexes(
+            fixing_index,
+            fixing_date,
+            fixing_rate,
+            curve_index,
+            curve_tenor,
+            curve_rate,
+            curve_date,
+        );
+        for (i, bond) in self.bonds.iter_mut().enumerate() {
+            if index[i].is_na() {
+                continue;
+            }
+            *bond = bond.take().and_then(|bond| {
+                if spread[i].is_na() || lookback[i].is_na() {
+                    return None;
+                }
+                let float = match rate_indexes.get(index[i]) {
+                    Some(Ok(value)) => float_rate::FloatCpn {
+                        index: value.clone(),
+                        spread: spread[i],
+                        reset_months: float_rate::to_reset_months(reset_freq[i]).ok()?,
+                        lookback: lookback[i] as i64,
+                    },
+                    _ => return None,
+                };
+                bond.with_float(Some(float)).ok()
+            });
+        }
+    }
+    // Link the bonds with a true `linker` to the CPI history of `cpi_date` and `cpi_value`
+    #[allow(clippy::too_many_arguments)]
+    fn with_linker(
+        &mut self,
+        linker: Robj,
+        base_cpi: Robj,
+        cpi_lag: Robj,
+        cpi_interp: Robj,
+        deflation_floor: Robj,
+        cpi_date: Robj,
+        cpi_value: Robj,
+    ) {
+        check_len!(
+            self.bonds,
+            linker,
+            base_cpi,
+            cpi_lag,
+            cpi_interp,
+            deflation_floor
+        );
+        let linker = linker.as_logical_slice().expect("linker must be logical");
+        let base_cpi = base_cpi.as_real_slice().expect("base_cpi must be double");
+        let cpi_lag = cpi_lag.as_integer_slice().expect("cpi_lag must be int");
+        let cpi_interp = cpi_interp
+            .as_logical_slice()
+            .expect("cpi_interp must be logical");
+        let deflation_floor = deflation_floor
+            .as_logical_slice()
+            .expect("deflation_floor must be logical");
+        check_len!(cpi_date, cpi_value);
+        let cpi_date = rdate::robj2date(cpi_date, "cpi_date").unwrap();
+        let cpi_value = cpi_value.as_real_slice().expect("cpi_value must be double");
+        let cpi: BTreeMap<NaiveDate, f64> = cpi_date
+            .iter()
+            .zip(cpi_value)
+            .filter_map(|(date, value)| match (date, value.is_na()) {
+                (Some(date), false) => Some((*date, *value)),
+                _ => None,
+            })
+            .collect();
+        let cpi = inflation::CpiIndex::new(cpi).map(Rc::new);
+        for (i, bond) in self.bonds.iter_mut().enumerate() {
+            if linker[i].is_false() {
+                continue;
+            }
+            *bond = bond.take().and_then(|bond| {
+                if linker[i].is_na()
+                    || cpi_lag[i].is_na()
+                    || cpi_interp[i].is_na()
+                    || deflation_floor[i].is_na()
+                {
+                    return None;
+                }
+                bond.with_linker(Some(inflation::Linker {
+                    cpi: cpi.clone().ok()?,
+                    base_cpi: if base_cpi[i].is_na() {
+                        None
+                    } else {
+                        Some(base_cpi[i])
+                    },
+                    lag: cpi_lag[i],
+                    interp: cpi_interp[i].is_true(),
+                    floor: deflation_floor[i].is_true(),
+                }))
+                .ok()
+            });
+        }
+    }
+    #[allow(clippy::too_many_arguments)]
+    fn new_perpetual(
+        value_date: Robj,
+        first_call_date: Robj,
+        redem_value: Robj,
+        cpn_rate: Robj,
+        cpn_freq: Robj,
+        reset_months: Robj,
+        benchmark: Robj,
+        init_spread: Robj,
+        step_up: Robj,
+        extensions: Robj,
+        day_count: Robj,
+        bdc: Robj,
+        holidays: Robj,
+    ) -> Self {
+        let n = value_date.len();
+        check_len!(
+            value_date,
+            first_call_date,
+            redem_value,
+            cpn_rate,
+            cpn_freq,
+            reset_months,
+            benchmark,
+            init_spread,
+            step_up,
+            extensions,
+            day_count,
+            bdc
+        );
+        let value_date = rdate::robj2date(value_date, "value_date").unwrap();
+        let first_call_date = rdate::robj2date(first_call_date, "first_call_date").unwrap();
+        let redem_value = redem_value
+            .as_real_slice()
+            .expect("redem_value must be double");
+        let cpn_rate = cpn_rate.as_real_slice().expect("cpn_rate must be double");
+        let cpn_freq = cpn_freq.as_integer_slice().expect("cpn_freq must be int");
+        let reset_months = reset_months
+            .as_integer_slice()
+            .expect("reset_months must be int");
+        let benchmark = benchmark.as_real_slice().expect("benchmark must be double");
+        let init_spread = init_spread
+            .as_real_slice()
+            .expect("init_spread must be double");
+        let step_up = step_up.as_real_slice().expect("step_up must be double");
+        let extensions = extensions
+            .as_integer_slice()
+            .expect("extensions must be int");
+        let day_count = day_count
+            .as_str_vector()
+            .expect("day_count must be character");
+        let bdc = bdc.as_str_vector().expect("bdc must be character");
+        let holidays: Vec<NaiveDate> = rdate::robj2date(holidays, "holidays")
+            .unwrap()
+            .into_iter()
+            .flatten()
+            .collect();
+        let calendar = Rc::new(calendar::Calendar::new(holidays));
+        let mut out: Vec<Option<bond::FixedBond>> = Vec::new();
+        for i in 0..n {
+            let (Some(value_date), Some(first_call_date)) = (value_date[i], first_call_date[i])
+            else {
+                out.push(None);
+                continue;
+            };
+            if redem_value[i].is_na()
+                || cpn_rate[i].is_na()
+                || cpn_freq[i].is_na()
+                || reset_months[i].is_na()
+                || benchmark[i].is_na()
+                || init_spread[i].is_na()
+                || step_up[i].is_na()
+                || extensions[i].is_na()
+                || extensions[i] < 0
+            {
+                out.push(None);
+                continue;
+            }
+            let perp = perpetual::Perpetual {
+                value_date,
+                first_call_date,
+                redem_value: redem_value[i],
+                cpn_rate: cpn_rate[i],
+                cpn_freq: cpn_freq[i],
+                reset_months: reset_months[i],
+                benchmark: benchmark[i],
+                init_spread: init_spread[i],
+                step_up: step_up[i],
+            };
+            let bond = perp.to_bond(extensions[i] as u32).and_then(|bond| {
+                let bond = bond
+                    .with_day_count(day_count::to_day_count(day_count[i])?)
+                    .with_calendar(calendar.clone(), calendar::to_bus_day_conv(bdc[i])?, false);
+                Ok(bond)
+            });
+            out.push(bond.ok());
+        }
+        RFixedBond { bonds: out }
+    }
+    fn ytm_dur(
+        &self,
+        ref_date: Robj,
+        clean_price: Robj,
+        settle_date: Robj,
+        convention: &str,
+    ) -> Robj {
+        check_len!(self.bonds, ref_date, clean_price, settle_date);
+        let convention = bond::to_convention(convention).unwrap();
         let ref_date = rdate::robj2date(ref_date, "ref_date").unwrap();
+        let settle_date = to_settle_dates(&self.bonds, &ref_date, settle_date);
         let clean_price = clean_price
             .as_real_slice()
             .expect("clean_price must be double");
@@ -71,6 +413,10 @@ impl RFixedBond {
             ytm: Vec<Option<f64>>,
             macd: Vec<Option<f64>>,
             modd: Vec<Option<f64>>,
+            cvx: Vec<Option<f64>>,
+            eff_cvx: Vec<Option<f64>>,
+            dv01: Vec<Option<f64>>,
+            pv01: Vec<Option<f64>>,
         }
         impl Out {
             fn new() -> Self {
@@ -78,64 +424,737 @@ impl RFixedBond {
                     ytm: Vec::new(),
                     macd: Vec::new(),
                     modd: Vec::new(),
+                    cvx: Vec::new(),
+                    eff_cvx: Vec::new(),
+                    dv01: Vec::new(),
+                    pv01: Vec::new(),
                 }
             }
             fn push_none(&mut self) {
                 self.ytm.push(None);
                 self.macd.push(None);
                 self.modd.push(None);
+                self.cvx.push(None);
+                self.eff_cvx.push(None);
+                self.dv01.push(None);
+                self.pv01.push(None);
             }
             fn push(&mut self, value: bond::BondVal) {
                 self.ytm.push(Some(value.ytm));
                 self.macd.push(Some(value.macd));
                 self.modd.push(Some(value.modd));
+                self.cvx.push(Some(value.cvx));
+                self.eff_cvx.push(Some(value.eff_cvx));
+                self.dv01.push(Some(value.dv01));
+                self.pv01.push(Some(value.pv01));
             }
         }
         let mut out = Out::new();
         for (i, bond) in self.bonds.iter().enumerate() {
-            if ref_date[i].is_none() || clean_price[i].is_na() {
-                out.push_none();
-                continue;
-            }
-            match bond {
-                Some(bond) => {
-                    let ref_date = ref_date[i].unwrap();
-                    match bond.result(&ref_date, clean_price[i]) {
+            match (bond, settle_date[i]) {
+                (Some(bond), Some(settle_date)) if !clean_price[i].is_na() => {
+                    match bond.result(&settle_date, clean_price[i], convention) {
                         Some(value) => {
                             out.push(value);
                         }
                         None => out.push_none(),
                     }
                 }
-                None => out.push_none(),
+                _ => out.push_none(),
+            }
+        }
+        data_frame!(
+            YTM = out.ytm,
+            MACD = out.macd,
+            MODD = out.modd,
+            CONVEXITY = out.cvx,
+            EFF_CONVEXITY = out.eff_cvx,
+            DV01 = out.dv01,
+            PV01 = out.pv01
+        )
+    }
+    fn price(&self, ref_date: Robj, ytm: Robj, settle_date: Robj, convention: &str) -> Robj {
+        check_len!(self.bonds, ref_date, ytm, settle_date);
+        let convention = bond::to_convention(convention).unwrap();
+        let ref_date = rdate::robj2date(ref_date, "ref_date").unwrap();
+        let settle_date = to_settle_dates(&self.bonds, &ref_date, settle_date);
+        let ytm = ytm.as_real_slice().expect("ytm must be double");
+        let mut clean_price: Vec<Option<f64>> = Vec::new();
+        let mut dirty_price: Vec<Option<f64>> = Vec::new();
+        let mut accrued: Vec<Option<f64>> = Vec::new();
+        for (i, bond) in self.bonds.iter().enumerate() {
+            let value = match (bond, settle_date[i]) {
+                (Some(bond), Some(settle_date)) if !ytm[i].is_na() => {
+                    bond.price(&settle_date, ytm[i], convention)
+                }
+                _ => None,
+            };
+            clean_price.push(value.as_ref().map(|v| v.clean_price));
+            dirty_price.push(value.as_ref().map(|v| v.dirty_price));
+            accrued.push(value.as_ref().map(|v| v.accrued));
+        }
+        data_frame!(
+            CLEAN_PRICE = clean_price,
+            DIRTY_PRICE = dirty_price,
+            ACCRUED = accrued
+        )
+    }
+    #[allow(clippy::too_many_arguments)]
+    fn horizon_return(
+        &self,
+        ref_date: Robj,
+        clean_price: Robj,
+        horizon_date: Robj,
+        reinvest_rate: Robj,
+        horizon_ytm: Robj,
+        spread_chg: Robj,
+        convention: &str,
+        settle_date: Robj,
+    ) -> Robj {
+        check_len!(
+            self.bonds,
+            ref_date,
+            clean_price,
+            horizon_date,
+            reinvest_rate,
+            horizon_ytm,
+            spread_chg,
+            settle_date
+        );
+        let convention = bond::to_convention(convention).unwrap();
+        let ref_date = rdate::robj2date(ref_date, "ref_date").unwrap();
+        let settle_date = to_settle_dates(&self.bonds, &ref_date, settle_date);
+        let horizon_date = rdate::robj2date(horizon_date, "horizon_date").unwrap();
+        let clean_price = clean_price
+            .as_real_slice()
+            .expect("clean_price must be double");
+        let reinvest_rate = reinvest_rate
+            .as_real_slice()
+            .expect("reinvest_rate must be double");
+        let horizon_ytm = horizon_ytm
+            .as_real_slice()
+            .expect("horizon_ytm must be double");
+        let spread_chg = spread_chg
+            .as_real_slice()
+            .expect("spread_chg must be double");
+        let mut income: Vec<Option<f64>> = Vec::new();
+        let mut reinvest: Vec<Option<f64>> = Vec::new();
+        let mut roll_down: Vec<Option<f64>> = Vec::new();
+        let mut price_chg: Vec<Option<f64>> = Vec::new();
+        let mut total: Vec<Option<f64>> = Vec::new();
+        let mut horizon_price: Vec<Option<f64>> = Vec::new();
+        for (i, bond) in self.bonds.iter().enumerate() {
+            let value = match (bond, settle_date[i], horizon_date[i]) {
+                (Some(bond), Some(settle_date), Some(horizon_date))
+                    if !clean_price[i].is_na()
+                        && !reinvest_rate[i].is_na()
+                        && !spread_chg[i].is_na() =>
+                {
+                    let exit_ytm = Some(horizon_ytm[i]).filter(|v| !v.is_na());
+                    bond.horizon_return(
+                        &settle_date,
+                        clean_price[i],
+                        &horizon_date,
+                        reinvest_rate[i],
+                        exit_ytm,
+                        spread_chg[i],
+                        convention,
+                    )
+                }
+                _ => None,
+            };
+            income.push(value.as_ref().map(|v| v.income));
+            reinvest.push(value.as_ref().map(|v| v.reinvest));
+            roll_down.push(value.as_ref().map(|v| v.roll_down));
+            price_chg.push(value.as_ref().map(|v| v.price_chg));
+            total.push(value.as_ref().map(|v| v.total));
+            horizon_price.push(value.as_ref().and_then(|v| v.horizon_price));
+        }
+        data_frame!(
+            INCOME = income,
+            REINVEST = reinvest,
+            ROLL_DOWN = roll_down,
+            PRICE_CHG = price_chg,
+            TOTAL_RETURN = total,
+            HORIZON_PRICE = horizon_price
+        )
+    }
+    fn carry_roll(
+        &self,
+        ref_date: Robj,
+        clean_price: Robj,
+        funding_rate: Robj,
+        curve: Robj,
+        horizons: Robj,
+        settle_date: Robj,
+    ) -> Robj {
+        check_len!(self.bonds, ref_date, clean_price, funding_rate, settle_date);
+        let ref_date = rdate::robj2date(ref_date, "ref_date").unwrap();
+        let settle_date = to_settle_dates(&self.bonds, &ref_date, settle_date);
+        let clean_price = clean_price
+            .as_real_slice()
+            .expect("clean_price must be double");
+        let funding_rate = funding_rate
+            .as_real_slice()
+            .expect("funding_rate must be double");
+        let curve: &RCurve = (&curve).try_into().expect("curve must be RCurve");
+        let horizons = horizons.as_integer_slice().expect("horizons must be int");
+        let mut ids: Vec<i32> = Vec::new();
+        let mut months: Vec<i32> = Vec::new();
+        let mut horizon_dates: Vec<Option<NaiveDate>> = Vec::new();
+        let mut carry: Vec<Option<f64>> = Vec::new();
+        let mut roll_down: Vec<Option<f64>> = Vec::new();
+        let mut breakeven: Vec<Option<f64>> = Vec::new();
+        for (i, bond) in self.bonds.iter().enumerate() {
+            for horizon in horizons {
+                let settle_date = settle_date[i];
+                let horizon_date = settle_date.map(|date| date_handle::add_months(&date, *horizon));
+                let value = match (bond, settle_date, horizon_date) {
+                    (Some(bond), Some(settle_date), Some(horizon_date))
+                        if !clean_price[i].is_na() && !funding_rate[i].is_na() =>
+                    {
+                        bond.carry_roll(
+                            &settle_date,
+                            clean_price[i],
+                            &horizon_date,
+                            funding_rate[i],
+                            &curve.curve,
+                            &curve.ref_date,
+                        )
+                    }
+                    _ => None,
+                };
+                ids.push((i + 1) as i32);
+                months.push(*horizon);
+                horizon_dates.push(horizon_date);
+                carry.push(value.as_ref().map(|v| v.carry));
+                roll_down.push(value.as_ref().map(|v| v.roll_down));
+                breakeven.push(value.as_ref().map(|v| v.breakeven));
+            }
+        }
+        data_frame!(
+            ID = ids,
+            HORIZON = months,
+            HORIZON_DATE = horizon_dates.to_rdate(),
+            CARRY = carry,
+            ROLL_DOWN = roll_down,
+            BREAKEVEN = breakeven
+        )
+    }
+    fn ytc(&self, ref_date: Robj, clean_price: Robj, settle_date: Robj, convention: &str) -> Robj {
+        check_len!(self.bonds, ref_date, clean_price, settle_date);
+        let convention = bond::to_convention(convention).unwrap();
+        let ref_date = rdate::robj2date(ref_date, "ref_date").unwrap();
+        let settle_date = to_settle_dates(&self.bonds, &ref_date, settle_date);
+        let clean_price = clean_price
+            .as_real_slice()
+            .expect("clean_price must be double");
+        let mut ids: Vec<i32> = Vec::new();
+        let mut call_dates: Vec<NaiveDate> = Vec::new();
+        let mut call_prices: Vec<f64> = Vec::new();
+        let mut ytcs: Vec<Option<f64>> = Vec::new();
+        for (i, bond) in self.bonds.iter().enumerate() {
+            if let (Some(bond), Some(settle_date)) = (bond, settle_date[i]) {
+                if clean_price[i].is_na() {
+                    continue;
+                }
+                for (call_date, call_price, ytc) in
+                    bond.ytc(&settle_date, clean_price[i], convention)
+                {
+                    ids.push(i as i32 + 1);
+                    call_dates.push(call_date);
+                    call_prices.push(call_price);
+                    ytcs.push(ytc);
+                }
+            }
+        }
+        data_frame!(
+            ID = ids,
+            CALL_DATE = call_dates.to_rdate(),
+            CALL_PRICE = call_prices,
+            YTC = ytcs
+        )
+    }
+    fn ytw(&self, ref_date: Robj, clean_price: Robj, settle_date: Robj, convention: &str) -> Robj {
+        check_len!(self.bonds, ref_date, clean_price, settle_date);
+        let convention = bond::to_convention(convention).unwrap();
+        let ref_date = rdate::robj2date(ref_date, "ref_date").unwrap();
+        let settle_date = to_settle_dates(&self.bonds, &ref_date, settle_date);
+        let clean_price = clean_price
+            .as_real_slice()
+            .expect("clean_price must be double");
+        let mut ytw: Vec<Option<f64>> = Vec::new();
+        let mut workout_date: Vec<Option<NaiveDate>> = Vec::new();
+        for (i, bond) in self.bonds.iter().enumerate() {
+            let value = match (bond, settle_date[i]) {
+                (Some(bond), Some(settle_date)) if !clean_price[i].is_na() => {
+                    bond.ytw(&settle_date, clean_price[i], convention)
+                }
+                _ => None,
+            };
+            ytw.push(value.map(|v| v.0));
+            workout_date.push(value.map(|v| v.1));
+        }
+        data_frame!(YTW = ytw, WORKOUT_DATE = workout_date.to_rdate())
+    }
+    fn oas(
+        &self,
+        ref_date: Robj,
+        clean_price: Robj,
+        curve: Robj,
+        vol: f64,
+        mean_rev: f64,
+        settle_date: Robj,
+    ) -> Robj {
+        check_len!(self.bonds, ref_date, clean_price, settle_date);
+        let ref_date = rdate::robj2date(ref_date, "ref_date").unwrap();
+        let settle_date = to_settle_dates(&self.bonds, &ref_date, settle_date);
+        let clean_price = clean_price
+            .as_real_slice()
+            .expect("clean_price must be double");
+        let curve: &RCurve = (&curve).try_into().expect("curve must be RCurve");
+        let model = lattice::HullWhite::new(mean_rev, vol).unwrap();
+        let mut oas: Vec<Option<f64>> = Vec::new();
+        let mut eff_dur: Vec<Option<f64>> = Vec::new();
+        let mut eff_cvx: Vec<Option<f64>> = Vec::new();
+        for (i, bond) in self.bonds.iter().enumerate() {
+            let value = match (bond, settle_date[i]) {
+                (Some(bond), Some(settle_date)) if !clean_price[i].is_na() => bond.oas(
+                    &settle_date,
+                    clean_price[i],
+                    &curve.curve,
+                    &curve.ref_date,
+                    &model,
+                ),
+                _ => None,
+            };
+            oas.push(value.as_ref().map(|v| v.oas));
+            eff_dur.push(value.as_ref().map(|v| v.eff_dur));
+            eff_cvx.push(value.as_ref().map(|v| v.eff_cvx));
+        }
+        data_frame!(OAS = oas, EFF_DUR = eff_dur, EFF_CONVEXITY = eff_cvx)
+    }
+    #[allow(clippy::too_many_arguments)]
+    fn spread(
+        &self,
+        ref_date: Robj,
+        clean_price: Robj,
+        curve: Robj,
+        swap_tenor: Robj,
+        swap_rate: Robj,
+        govt_tenor: Robj,
+        govt_rate: Robj,
+        convention: &str,
+        swap_convention: &str,
+        govt_convention: &str,
+        settle_date: Robj,
+    ) -> Robj {
+        check_len!(self.bonds, ref_date, clean_price, settle_date);
+        check_len!(swap_tenor, swap_rate);
+        check_len!(govt_tenor, govt_rate);
+        let convention = bond::to_convention(convention).unwrap();
+        let swap_convention = bond::to_convention(swap_convention).unwrap();
+        let govt_convention = bond::to_convention(govt_convention).unwrap();
+        let ref_date = rdate::robj2date(ref_date, "ref_date").unwrap();
+        let settle_date = to_settle_dates(&self.bonds, &ref_date, settle_date);
+        let clean_price = clean_price
+            .as_real_slice()
+            .expect("clean_price must be double");
+        let curve: Option<&RCurve> = if curve.is_null() {
+            None
+        } else {
+            Some((&curve).try_into().expect("curve must be RCurve"))
+        };
+        // the par rates by the tenors, linearly interpolated
+        let to_curve = |tenors: Robj, rates: Robj| {
+            let tenors = tenors.as_real_vector().expect("tenors must be double");
+            let rates = rates.as_real_vector().expect("rates must be double");
+            if tenors.is_empty() {
+                None
+            } else {
+                Some(curve::ZeroCurve::new(tenors, rates).unwrap())
+            }
+        };
+        let swap_curve = to_curve(swap_tenor, swap_rate);
+        let govt_curve = to_curve(govt_tenor, govt_rate);
+        let mut ytm: Vec<Option<f64>> = Vec::new();
+        let mut z_spread: Vec<Option<f64>> = Vec::new();
+        let mut i_spread: Vec<Option<f64>> = Vec::new();
+        let mut g_spread: Vec<Option<f64>> = Vec::new();
+        for (i, bond) in self.bonds.iter().enumerate() {
+            let (bond, ref_date) = match (bond, settle_date[i]) {
+                (Some(bond), Some(settle_date)) if !clean_price[i].is_na() => (bond, settle_date),
+                _ => {
+                    ytm.push(None);
+                    z_spread.push(None);
+                    i_spread.push(None);
+                    g_spread.push(None);
+                    continue;
+                }
+            };
+            let value = bond
+                .result(&ref_date, clean_price[i], convention)
+                .map(|v| v.ytm);
+            let tenor = date_handle::year_frac(&bond.mty_date(), &ref_date);
+            // the yield in the quoting convention of the curve, over the curve rate
+            let over = |curve: &Option<curve::ZeroCurve>, conv: bond::Convention| {
+                let rate = curve.as_ref()?.zero(tenor);
+                let value = bond.result(&ref_date, clean_price[i], conv)?.ytm;
+                Some(value - rate)
+            };
+            ytm.push(value);
+            z_spread.push(curve.and_then(|curve| {
+                bond.z_spread(&ref_date, clean_price[i], &curve.curve, &curve.ref_date)
+            }));
+            i_spread.push(over(&swap_curve, swap_convention));
+            g_spread.push(over(&govt_curve, govt_convention));
+        }
+        data_frame!(
+            YTM = ytm,
+            Z_SPREAD = z_spread,
+            I_SPREAD = i_spread,
+            G_SPREAD = g_spread
+        )
+    }
+    fn krd(
+        &self,
+        ref_date: Robj,
+        clean_price: Robj,
+        curve: Robj,
+        tenors: Robj,
+        settle_date: Robj,
+    ) -> Robj {
+        check_len!(self.bonds, ref_date, clean_price, settle_date);
+        let ref_date = rdate::robj2date(ref_date, "ref_date").unwrap();
+        let settle_date = to_settle_dates(&self.bonds, &ref_date, settle_date);
+        let clean_price = clean_price
+            .as_real_slice()
+            .expect("clean_price must be double");
+        let curve: &RCurve = (&curve).try_into().expect("curve must be RCurve");
+        let tenors = tenors.as_real_vector().expect("tenors must be double");
+        assert!(
+            !tenors.is_empty() && tenors.windows(2).all(|w| w[0] < w[1]),
+            "tenors must be non-empty and strictly increasing"
+        );
+        let mut ids: Vec<i32> = Vec::new();
+        let mut key_tenors: Vec<f64> = Vec::new();
+        let mut krds: Vec<Option<f64>> = Vec::new();
+        for (i, bond) in self.bonds.iter().enumerate() {
+            let value = match (bond, settle_date[i]) {
+                (Some(bond), Some(settle_date)) if !clean_price[i].is_na() => bond
+                    .key_rate_durations(
+                        &settle_date,
+                        clean_price[i],
+                        &curve.curve,
+                        &curve.ref_date,
+                        &tenors,
+                    ),
+                _ => None,
+            };
+            for (k, tenor) in tenors.iter().enumerate() {
+                ids.push((i + 1) as i32);
+                key_tenors.push(*tenor);
+                krds.push(value.as_ref().map(|v| v[k]));
+            }
+        }
+        data_frame!(ID = ids, TENOR = key_tenors, KRD = krds)
+    }
+    #[allow(clippy::too_many_arguments)]
+    fn futures_basis(
+        &self,
+        ref_date: Robj,
+        clean_price: Robj,
+        contract: &str,
+        delivery_date: Robj,
+        futures_price: Robj,
+        repo_rate: Robj,
+        settle_date: Robj,
+    ) -> Robj {
+        check_len!(
+            self.bonds,
+            ref_date,
+            clean_price,
+            futures_price,
+            repo_rate,
+            settle_date
+        );
+        let contract = futures::to_contract(contract).unwrap();
+        let ref_date = rdate::robj2date(ref_date, "ref_date").unwrap();
+        let settle_date = to_settle_dates(&self.bonds, &ref_date, settle_date);
+        let delivery_date = match rdate::robj2date(delivery_date, "delivery_date").unwrap()[..] {
+            [Some(date)] => date,
+            _ => panic!("delivery_date must be a non-NA scalar"),
+        };
+        let clean_price = clean_price
+            .as_real_slice()
+            .expect("clean_price must be double");
+        let futures_price = futures_price
+            .as_real_slice()
+            .expect("futures_price must be double");
+        let repo_rate = repo_rate.as_real_slice().expect("repo_rate must be double");
+        let values: Vec<Option<futures::FuturesBasis>> = self
+            .bonds
+            .iter()
+            .enumerate()
+            .map(|(i, bond)| match (bond, settle_date[i]) {
+                (Some(bond), Some(settle_date))
+                    if !clean_price[i].is_na()
+                        && !futures_price[i].is_na()
+                        && !repo_rate[i].is_na() =>
+                {
+                    futures::basis(
+                        bond,
+                        &settle_date,
+                        clean_price[i],
+                        &delivery_date,
+                        futures_price[i],
+                        repo_rate[i],
+                        contract,
+                    )
+                }
+                _ => None,
+            })
+            .collect();
+        let irr: Vec<Option<f64>> = values.iter().map(|v| v.as_ref().map(|v| v.irr)).collect();
+        // the cheapest-to-deliver of the basket on each settlement date
+        let mut baskets: BTreeMap<NaiveDate, Vec<usize>> = BTreeMap::new();
+        for (i, date) in settle_date.iter().enumerate() {
+            if let Some(date) = date {
+                baskets.entry(*date).or_default().push(i);
+            }
+        }
+        let mut ctd = vec![false; values.len()];
+        for basket in baskets.values() {
+            let irrs: Vec<Option<f64>> = basket.iter().map(|i| irr[*i]).collect();
+            if let Some(k) = futures::cheapest(&irrs) {
+                ctd[basket[k]] = true;
+            }
+        }
+        let field = |f: fn(&futures::FuturesBasis) -> Option<f64>| -> Vec<Option<f64>> {
+            values.iter().map(|v| v.as_ref().and_then(f)).collect()
+        };
+        data_frame!(
+            CF = field(|v| Some(v.cf)),
+            GROSS_BASIS = field(|v| Some(v.gross_basis)),
+            CARRY = field(|v| Some(v.carry)),
+            NET_BASIS = field(|v| Some(v.net_basis)),
+            IRR = irr,
+            DV01 = field(|v| v.dv01),
+            CTD = ctd
+        )
+    }
+    fn dm(&self, ref_date: Robj, clean_price: Robj, settle_date: Robj) -> Robj {
+        check_len!(self.bonds, ref_date, clean_price, settle_date);
+        let ref_date = rdate::robj2date(ref_date, "ref_date").unwrap();
+        let settle_date = to_settle_dates(&self.bonds, &ref_date, settle_date);
+        let clean_price = clean_price
+            .as_real_slice()
+            .expect("clean_price must be double");
+        let mut dm: Vec<Option<f64>> = Vec::new();
+        let mut spread_dur: Vec<Option<f64>> = Vec::new();
+        for (i, bond) in self.bonds.iter().enumerate() {
+            let value = match (bond, settle_date[i]) {
+                (Some(bond), Some(settle_date)) if !clean_price[i].is_na() => {
+                    bond.discount_margin(&settle_date, clean_price[i])
+                }
+                _ => None,
+            };
+            dm.push(value.as_ref().map(|v| v.dm));
+            spread_dur.push(value.as_ref().map(|v| v.spread_dur));
+        }
+        data_frame!(DM = dm, SPREAD_DUR = spread_dur)
+    }
+    fn index_ratio(&self, ref_date: Robj, settle_date: Robj) -> Robj {
+        check_len!(self.bonds, ref_date, settle_date);
+        let ref_date = rdate::robj2date(ref_date, "ref_date").unwrap();
+        let settle_date = to_settle_dates(&self.bonds, &ref_date, settle_date);
+        let index_ratio: Vec<Option<f64>> = self
+            .bonds
+            .iter()
+            .zip(settle_date)
+            .map(|(bond, settle_date)| match (bond, settle_date) {
+                (Some(bond), Some(settle_date)) => bond.index_ratio(&settle_date),
+                _ => None,
+            })
+            .collect();
+        data_frame!(INDEX_RATIO = index_ratio)
+    }
+    fn breakeven(
+        &self,
+        ref_date: Robj,
+        clean_price: Robj,
+        nominal_ytm: Robj,
+        settle_date: Robj,
+    ) -> Robj {
+        check_len!(self.bonds, ref_date, clean_price, nominal_ytm, settle_date);
+        let ref_date = rdate::robj2date(ref_date, "ref_date").unwrap();
+        let settle_date = to_settle_dates(&self.bonds, &ref_date, settle_date);
+        let clean_price = clean_price
+            .as_real_slice()
+            .expect("clean_price must be double");
+        let nominal_ytm = nominal_ytm
+            .as_real_slice()
+            .expect("nominal_ytm must be double");
+        let mut real_ytm: Vec<Option<f64>> = Vec::new();
+        let mut breakeven: Vec<Option<f64>> = Vec::new();
+        for (i, bond) in self.bonds.iter().enumerate() {
+            let value = match (bond, settle_date[i]) {
+                (Some(bond), Some(settle_date))
+                    if !clean_price[i].is_na() && !nominal_ytm[i].is_na() =>
+                {
+                    bond.breakeven(&settle_date, clean_price[i], nominal_ytm[i])
+                }
+                _ => None,
+            };
+            real_ytm.push(value.map(|v| v.0));
+            breakeven.push(value.map(|v| v.1));
+        }
+        data_frame!(REAL_YTM = real_ytm, BREAKEVEN = breakeven)
+    }
+    fn inflation_cf(&self, ref_date: Robj, settle_date: Robj) -> Robj {
+        check_len!(self.bonds, ref_date, settle_date);
+        let ref_date = rdate::robj2date(ref_date, "ref_date").unwrap();
+        let settle_date = to_settle_dates(&self.bonds, &ref_date, settle_date);
+        let mut ids: Vec<i32> = Vec::new();
+        let mut dates: Vec<NaiveDate> = Vec::new();
+        let mut index_ratios: Vec<Option<f64>> = Vec::new();
+        let mut cpns: Vec<f64> = Vec::new();
+        let mut redems: Vec<f64> = Vec::new();
+        for (i, bond) in self.bonds.iter().enumerate() {
+            if let (Some(bond), Some(settle_date)) = (bond, settle_date[i]) {
+                let cf = bond
+                    .inflation_cashflow(bond::BondCfType::Coupon)
+                    .cf(&settle_date, None);
+                cpns.append(&mut cf.values());
+                let cf = bond
+                    .inflation_cashflow(bond::BondCfType::Redem)
+                    .cf(&settle_date, None);
+                redems.append(&mut cf.values());
+                index_ratios.extend(cf.dates().iter().map(|date| bond.index_ratio(date)));
+                ids.append(&mut vec![i as i32 + 1; cf.len()]);
+                dates.append(&mut cf.dates());
             }
         }
-        data_frame!(YTM = out.ytm, MACD = out.macd, MODD = out.modd)
+        data_frame!(
+            ID = ids,
+            DATE = dates.to_rdate(),
+            INDEX_RATIO = index_ratios,
+            COUPON = cpns,
+            REDEM = redems
+        )
+    }
+    fn fit_nss(
+        &self,
+        ref_date: Robj,
+        clean_price: Robj,
+        svensson: bool,
+        objective: &str,
+        settle_date: Robj,
+    ) -> Robj {
+        check_len!(self.bonds, clean_price, settle_date);
+        let ref_date = match rdate::robj2date(ref_date, "ref_date").unwrap()[..] {
+            [Some(date)] => date,
+            _ => panic!("ref_date must be a non-NA scalar"),
+        };
+        let settle_date = to_settle_dates(
+            &self.bonds,
+            &vec![Some(ref_date); self.bonds.len()],
+            settle_date,
+        );
+        let clean_price = clean_price
+            .as_real_slice()
+            .expect("clean_price must be double");
+        let objective = nss::to_objective(objective).unwrap();
+        let bonds: Vec<(&bond::FixedBond, NaiveDate, f64)> = self
+            .bonds
+            .iter()
+            .enumerate()
+            .filter_map(|(i, bond)| match (bond, settle_date[i]) {
+                (Some(bond), Some(settle_date)) if !clean_price[i].is_na() => {
+                    Some((bond, settle_date, clean_price[i]))
+                }
+                _ => None,
+            })
+            .collect();
+        let nss = nss::fit(&ref_date, &bonds, svensson, objective).unwrap();
+        let mut ytm: Vec<Option<f64>> = Vec::new();
+        let mut fitted_ytm: Vec<Option<f64>> = Vec::new();
+        let mut fitted_price: Vec<Option<f64>> = Vec::new();
+        for (i, bond) in self.bonds.iter().enumerate() {
+            let value = match (bond, settle_date[i]) {
+                (Some(bond), Some(settle_date)) if !clean_price[i].is_na() => {
+                    let dirty_price = nss.dirty_price(bond, &ref_date, &settle_date);
+                    let price = bond.clean_price(&settle_date, dirty_price);
+                    let ytm_of = |price: f64| {
+                        bond.result(&settle_date, price, bond::Convention::Annual)
+                            .map(|v| v.ytm)
+                    };
+                    Some((ytm_of(clean_price[i]), ytm_of(price), price))
+                }
+                _ => None,
+            };
+            ytm.push(value.and_then(|v| v.0));
+            fitted_ytm.push(value.and_then(|v| v.1));
+            fitted_price.push(value.map(|v| v.2));
+        }
+        let residual: Vec<Option<f64>> = ytm
+            .iter()
+            .zip(&fitted_ytm)
+            .map(|(ytm, fitted_ytm)| Some((*ytm)? - (*fitted_ytm)?))
+            .collect();
+        let params = data_frame!(
+            BETA0 = vec![nss.beta0],
+            BETA1 = vec![nss.beta1],
+            BETA2 = vec![nss.beta2],
+            BETA3 = vec![if svensson { Some(nss.beta3) } else { None }],
+            TAU1 = vec![nss.tau1],
+            TAU2 = vec![if svensson { Some(nss.tau2) } else { None }]
+        );
+        let fitted = data_frame!(
+            YTM = ytm,
+            FITTED_YTM = fitted_ytm,
+            FITTED_PRICE = fitted_price,
+            RESIDUAL = residual
+        );
+        list!(params = params, fitted = fitted).into()
     }
     fn len(&self) -> i32 {
         self.bonds.len() as i32
     }
-    fn cf(&self, ref_date: Robj) -> Robj {
-        check_len!(self.bonds, ref_date);
+    fn cf(&self, ref_date: Robj, settle_date: Robj) -> Robj {
+        check_len!(self.bonds, ref_date, settle_date);
         let ref_date = rdate::robj2date(ref_date, "ref_date").unwrap();
+        let settle_date = to_settle_dates(&self.bonds, &ref_date, settle_date);
         let mut ids: Vec<i32> = Vec::new();
         let mut dates: Vec<NaiveDate> = Vec::new();
+        let mut accrual_dates: Vec<NaiveDate> = Vec::new();
         let mut cpns: Vec<f64> = Vec::new();
         let mut redems: Vec<f64> = Vec::new();
         for (i, bond) in self.bonds.iter().enumerate() {
-            if ref_date[i].is_none() {
+            if settle_date[i].is_none() {
                 continue;
             }
             match bond {
                 Some(value) => {
                     let cf = value
                         .cashflow(bond::BondCfType::Coupon)
-                        .cf(&ref_date[i].unwrap(), None);
+                        .cf(&settle_date[i].unwrap(), None);
                     cpns.append(&mut cf.values());
                     let cf = value
                         .cashflow(bond::BondCfType::Redem)
-                        .cf(&ref_date[i].unwrap(), None);
+                        .cf(&settle_date[i].unwrap(), None);
                     redems.append(&mut cf.values());
+                    let schedule: BTreeMap<NaiveDate, NaiveDate> = value
+                        .pay_schedule()
+                        .into_iter()
+                        .map(|(accrual_date, pay_date)| (pay_date, accrual_date))
+                        .collect();
+                    // the principal may be repaid on a date that is not a coupon date
+                    accrual_dates.extend(
+                        cf.dates()
+                            .iter()
+                            .map(|date| schedule.get(date).copied().unwrap_or(*date)),
+                    );
                     dates.append(&mut cf.dates());
                     ids.append(&mut vec![i as i32 + 1; cf.len()]);
                 }
@@ -145,12 +1164,303 @@ impl RFixedBond {
         data_frame!(
             ID = ids,
             DATE = dates.to_rdate(),
+            ACCRUAL_DATE = accrual_dates.to_rdate(),
             COUPON = cpns,
             REDEM = redems
         )
     }
 }
 
+// The settlement dates of the trades on `ref_date`, which are `settle_date` if it's not `NA`
+// or follow the settlement lags of the bonds
+fn to_settle_dates(
+    bonds: &[Option<bond::FixedBond>],
+    ref_date: &[Option<NaiveDate>],
+    settle_date: Robj,
+) -> Vec<Option<NaiveDate>> {
+    let settle_date = rdate::robj2date(settle_date, "settle_date").unwrap();
+    bonds
+        .iter()
+        .zip(ref_date)
+        .zip(settle_date)
+        .map(|((bond, ref_date), settle_date)| match (bond, ref_date) {
+            (Some(bond), Some(ref_date)) => {
+                Some(settle_date.unwrap_or_else(|| bond.settle_date(ref_date)))
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+// Group a long-format schedule (bond index, date, value) by the 1-based bond index
+fn to_schedules(
+    ids: Robj,
+    dates: Robj,
+    values: Robj,
+    var: &str,
+) -> BTreeMap<i32, BTreeMap<NaiveDate, f64>> {
+    check_len!(ids, dates, values);
+    let ids = ids
+        .as_integer_slice()
+        .unwrap_or_else(|| panic!("{}_id must be int", var));
+    let dates = rdate::robj2date(dates, &format!("{}_date", var)).unwrap();
+    let values = values
+        .as_real_slice()
+        .unwrap_or_else(|| panic!("{}_value must be double", var));
+    let mut out: BTreeMap<i32, BTreeMap<NaiveDate, f64>> = BTreeMap::new();
+    for i in 0..ids.len() {
+        if ids[i].is_na() || dates[i].is_none() || values[i].is_na() {
+            continue;
+        }
+        *out.entry(ids[i])
+            .or_default()
+            .entry(dates[i].unwrap())
+            .or_insert(0.0) += values[i];
+    }
+    out
+}
+
+// The rate indexes keyed by the names, built from the long-format fixings and forward
+// curves, where all the forward curves are as of `curve_date`
+fn to_rate_indexes(
+    fixing_index: Robj,
+    fixing_date: Robj,
+    fixing_rate: Robj,
+    curve_index: Robj,
+    curve_tenor: Robj,
+    curve_rate: Robj,
+    curve_date: Robj,
+) -> BTreeMap<String, std::result::Result<Rc<float_rate::RateIndex>, String>> {
+    check_len!(fixing_index, fixing_date, fixing_rate);
+    check_len!(curve_index, curve_tenor, curve_rate);
+    let fixing_index = fixing_index
+        .as_str_vector()
+        .expect("fixing_index must be character");
+    let fixing_date = rdate::robj2date(fixing_date, "fixing_date").unwrap();
+    let fixing_rate = fixing_rate
+        .as_real_slice()
+        .expect("fixing_rate must be double");
+    let curve_index = curve_index
+        .as_str_vector()
+        .expect("curve_index must be character");
+    let curve_tenor = curve_tenor
+        .as_real_slice()
+        .expect("curve_tenor must be double");
+    let curve_rate = curve_rate
+        .as_real_slice()
+        .expect("curve_rate must be double");
+    let curve_date = rdate::robj2date(curve_date, "curve_date").unwrap();
+    let curve_date = curve_date.first().cloned().flatten();
+    let mut fixings: BTreeMap<String, BTreeMap<NaiveDate, f64>> = BTreeMap::new();
+    for i in 0..fixing_index.len() {
+        if let (false, Some(date), false) = (
+            fixing_index[i].is_na(),
+            fixing_date[i],
+            fixing_rate[i].is_na(),
+        ) {
+            fixings
+                .entry(fixing_index[i].to_string())
+                .or_default()
+                .insert(date, fixing_rate[i]);
+        }
+    }
+    let mut curves: BTreeMap<String, BTreeMap<i64, (f64, f64)>> = BTreeMap::new();
+    for i in 0..curve_index.len() {
+        if !curve_index[i].is_na() && !curve_tenor[i].is_na() && !curve_rate[i].is_na() {
+            // keyed by the tenor in days, so the points are sorted
+            curves
+                .entry(curve_index[i].to_string())
+                .or_default()
+                .insert(
+                    (curve_tenor[i] * 365.0).round() as i64,
+                    (curve_tenor[i], curve_rate[i]),
+                );
+        }
+    }
+    let names: Vec<String> = fixings.keys().chain(curves.keys()).cloned().collect();
+    names
+        .into_iter()
+        .map(|name| {
+            let fwd_curve = match (curves.remove(&name), curve_date) {
+                (Some(points), Some(date)) => {
+                    let (tenors, rates) = points.into_values().unzip();
+                    curve::ZeroCurve::new(tenors, rates).map(|curve| Some((date, curve)))
+                }
+                (Some(_), None) => Err("curve_date must be provided".to_string()),
+                (None, _) => Ok(None),
+            };
+            let index = fwd_curve.and_then(|fwd_curve| {
+                float_rate::RateIndex::new(fixings.remove(&name).unwrap_or_default(), fwd_curve)
+                    .map(Rc::new)
+            });
+            (name, index)
+        })
+        .collect()
+}
+
+#[extendr]
+struct RCurve {
+    ref_date: NaiveDate,
+    pillars: Vec<NaiveDate>,
+    curve: curve::ZeroCurve,
+}
+
+impl RCurve {
+    fn tenor(&self, date: &NaiveDate) -> f64 {
+        date_handle::year_frac(date, &self.ref_date)
+    }
+}
+
+#[extendr]
+impl RCurve {
+    fn new(ref_date: Robj, dates: Robj, values: Robj, is_df: bool, interp: &str) -> Self {
+        check_len!(dates, values);
+        let ref_date =
+            rdate::robj2date(ref_date, "ref_date").unwrap()[0].expect("ref_date must not be NA");
+        let pillars: Vec<NaiveDate> = rdate::robj2date(dates, "dates")
+            .unwrap()
+            .into_iter()
+            .map(|date| date.expect("dates must not be NA"))
+            .collect();
+        let values = values.as_real_slice().expect("values must be double");
+        let tenors: Vec<f64> = pillars
+            .iter()
+            .map(|date| date_handle::year_frac(date, &ref_date))
+            .collect();
+        let rates: Vec<f64> = tenors
+            .iter()
+            .zip(values)
+            .map(|(t, v)| if is_df { -v.ln() / t } else { *v })
+            .collect();
+        let curve = curve::ZeroCurve::new(tenors, rates)
+            .unwrap()
+            .with_interp(curve::to_interp(interp).unwrap());
+        RCurve {
+            ref_date,
+            pillars,
+            curve,
+        }
+    }
+    #[allow(clippy::too_many_arguments)]
+    fn bootstrap(
+        ref_date: Robj,
+        dep_date: Robj,
+        dep_rate: Robj,
+        par_date: Robj,
+        par_yield: Robj,
+        par_freq: Robj,
+        bonds: Robj,
+        clean_price: Robj,
+        interp: &str,
+    ) -> Self {
+        check_len!(dep_date, dep_rate);
+        check_len!(par_date, par_yield, par_freq);
+        let ref_date =
+            rdate::robj2date(ref_date, "ref_date").unwrap()[0].expect("ref_date must not be NA");
+        let dep_date = rdate::robj2date(dep_date, "dep_date").unwrap();
+        let dep_rate = dep_rate.as_real_slice().expect("dep_rate must be double");
+        let par_date = rdate::robj2date(par_date, "par_date").unwrap();
+        let par_yield = par_yield.as_real_slice().expect("par_yield must be double");
+        let par_freq = par_freq.as_integer_slice().expect("par_freq must be int");
+        let par_bonds: Vec<(bond::FixedBond, f64)> = par_date
+            .iter()
+            .zip(par_yield.iter().zip(par_freq))
+            .map(|(date, (par_yield, par_freq))| {
+                let bond = bond::FixedBond::new(
+                    ref_date,
+                    date.expect("par_date must not be NA"),
+                    100.0,
+                    *par_yield,
+                    *par_freq,
+                )
+                .unwrap();
+                (bond, 100.0)
+            })
+            .collect();
+        let bonds: Vec<(&bond::FixedBond, f64)> = if bonds.is_null() {
+            Vec::new()
+        } else {
+            let bonds: &RFixedBond = (&bonds).try_into().expect("bonds must be RFixedBond");
+            check_len!(bonds.bonds, clean_price);
+            let clean_price = clean_price
+                .as_real_slice()
+                .expect("clean_price must be double");
+            bonds
+                .bonds
+                .iter()
+                .zip(clean_price)
+                .filter_map(|(bond, clean_price)| match bond {
+                    Some(bond) if !clean_price.is_na() => {
+                        Some((bond, bond.dirty_price(&ref_date, *clean_price)))
+                    }
+                    _ => None,
+                })
+                .collect()
+        };
+        let mut instruments: Vec<bootstrap::Instrument> = dep_date
+            .iter()
+            .zip(dep_rate)
+            .map(|(date, rate)| {
+                bootstrap::Instrument::Deposit(date.expect("dep_date must not be NA"), *rate)
+            })
+            .collect();
+        instruments.extend(
+            par_bonds
+                .iter()
+                .map(|(bond, price)| bootstrap::Instrument::Bond(bond, *price)),
+        );
+        instruments.extend(
+            bonds
+                .into_iter()
+                .map(|(bond, price)| bootstrap::Instrument::Bond(bond, price)),
+        );
+        let (pillars, curve) =
+            bootstrap::bootstrap(&ref_date, &instruments, curve::to_interp(interp).unwrap())
+                .unwrap();
+        RCurve {
+            ref_date,
+            pillars,
+            curve,
+        }
+    }
+    fn df(&self, date: Robj) -> Vec<Option<f64>> {
+        rdate::robj2date(date, "date")
+            .unwrap()
+            .into_iter()
+            .map(|date| date.map(|date| self.curve.df(self.tenor(&date))))
+            .collect()
+    }
+    fn zero(&self, date: Robj) -> Vec<Option<f64>> {
+        rdate::robj2date(date, "date")
+            .unwrap()
+            .into_iter()
+            .map(|date| date.map(|date| self.curve.zero(self.tenor(&date))))
+            .collect()
+    }
+    fn fwd(&self, from: Robj, to: Robj) -> Vec<Option<f64>> {
+        check_len!(from, to);
+        let from = rdate::robj2date(from, "from").unwrap();
+        let to = rdate::robj2date(to, "to").unwrap();
+        from.into_iter()
+            .zip(to)
+            .map(|(from, to)| match (from, to) {
+                (Some(from), Some(to)) => Some(self.curve.fwd(self.tenor(&from), self.tenor(&to))),
+                _ => None,
+            })
+            .collect()
+    }
+    fn pillars(&self) -> Robj {
+        let tenors = self.curve.tenors().to_vec();
+        let dfs: Vec<f64> = tenors.iter().map(|t| self.curve.df(*t)).collect();
+        data_frame!(
+            DATE = self.pillars.to_rdate(),
+            TENOR = tenors,
+            ZERO = self.curve.rates().to_vec(),
+            DF = dfs
+        )
+    }
+}
+
 #[extendr]
 struct RRtn {
     data: BTreeMap<i32, rtn::Rtn>,
@@ -241,5 +1551,6 @@ impl RRtn {
 extendr_module! {
     mod fcl;
     impl RFixedBond;
+    impl RCurve;
     impl RRtn;
 }
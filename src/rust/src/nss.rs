@@ -0,0 +1,269 @@
+use crate::bond::{BondCfType, Convention, FixedBond};
+use crate::date_handle;
+use crate::optimize::nelder_mead;
+use chrono::NaiveDate;
+
+// The initial decay factors of the multi-start fitting, as the objective is not convex in them
+const TAU1_GUESSES: [f64; 4] = [0.5, 1.0, 2.0, 5.0];
+const TAU2_GUESSES: [f64; 2] = [5.0, 10.0];
+
+// The pricing errors minimized by the fitting
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Objective {
+    // the dirty price errors
+    Price,
+    // the price errors divided by the dollar durations, i.e., the (approximate) yield errors
+    Yield,
+}
+
+pub fn to_objective(objective: &str) -> Result<Objective, String> {
+    match objective.to_lowercase().as_str() {
+        "price" => Ok(Objective::Price),
+        "yield" => Ok(Objective::Yield),
+        _ => Err(format!("objective({}) is undefined", objective)),
+    }
+}
+
+// The Nelson-Siegel-Svensson curve of the continuously compounded zero rates. It's the
+// Nelson-Siegel curve when `beta3` is zero.
+#[derive(Debug, Copy, Clone)]
+pub struct Nss {
+    pub beta0: f64,
+    pub beta1: f64,
+    pub beta2: f64,
+    pub beta3: f64,
+    pub tau1: f64,
+    pub tau2: f64,
+}
+
+impl Nss {
+    pub fn zero(&self, t: f64) -> f64 {
+        // (1 - exp(-x)) / x and its limit 1 at zero
+        let slope = |x: f64| {
+            if x < 1e-8 {
+                1.0
+            } else {
+                (1.0 - (-x).exp()) / x
+            }
+        };
+        let hump = |x: f64| slope(x) - (-x).exp();
+        let (x1, x2) = (t / self.tau1, t / self.tau2);
+        self.beta0 + self.beta1 * slope(x1) + self.beta2 * hump(x1) + self.beta3 * hump(x2)
+    }
+    pub fn df(&self, t: f64) -> f64 {
+        (-self.zero(t) * t).exp()
+    }
+    // The model dirty price of `bond` on `settle_date`, discounted along the curve as of
+    // `ref_date`
+    pub fn dirty_price(
+        &self,
+        bond: &FixedBond,
+        ref_date: &NaiveDate,
+        settle_date: &NaiveDate,
+    ) -> f64 {
+        self.pv(&FitBond::cashflows(bond, ref_date, settle_date))
+    }
+    // The value on the settlement date of the cashflows keyed by the years from `ref_date`,
+    // where the first one is the settlement
+    fn pv(&self, cfs: &[(f64, f64)]) -> f64 {
+        let pv: f64 = cfs[1..].iter().map(|(t, cf)| cf * self.df(*t)).sum();
+        pv / self.df(cfs[0].0)
+    }
+    // The curve from the optimizer's parameters, where the decay factors are in logs so that
+    // they stay positive
+    fn from_params(x: &[f64]) -> Self {
+        let tau1 = x[3].exp();
+        match x.len() {
+            4 => Nss {
+                beta0: x[0],
+                beta1: x[1],
+                beta2: x[2],
+                beta3: 0.0,
+                tau1,
+                tau2: tau1,
+            },
+            _ => Nss {
+                beta0: x[0],
+                beta1: x[1],
+                beta2: x[2],
+                beta3: x[4],
+                tau1,
+                tau2: x[5].exp(),
+            },
+        }
+    }
+}
+
+// The bond to fit, with its cashflows, the market dirty price and the weight of the price error
+struct FitBond {
+    cfs: Vec<(f64, f64)>,
+    dirty_price: f64,
+    weight: f64,
+}
+
+impl FitBond {
+    // The cashflows after `settle_date` keyed by the years from `ref_date`, following the
+    // settlement date with a zero cashflow
+    fn cashflows(
+        bond: &FixedBond,
+        ref_date: &NaiveDate,
+        settle_date: &NaiveDate,
+    ) -> Vec<(f64, f64)> {
+        let cf = bond.cashflow(BondCfType::All).cf(settle_date, None);
+        let mut cfs = vec![(date_handle::year_frac(settle_date, ref_date), 0.0)];
+        cfs.extend(
+            cf.dates()
+                .iter()
+                .zip(cf.values())
+                .map(|(date, cf)| (date_handle::year_frac(date, ref_date), cf)),
+        );
+        cfs
+    }
+}
+
+// Fits the Nelson-Siegel (or Svensson if `svensson`) curve as of `ref_date` to the bonds, their
+// settlement dates and clean prices, by minimizing the sum of the squared `objective` errors.
+// The bonds whose yields can't be solved, e.g., the matured ones, are left out.
+pub fn fit(
+    ref_date: &NaiveDate,
+    bonds: &[(&FixedBond, NaiveDate, f64)],
+    svensson: bool,
+    objective: Objective,
+) -> Result<Nss, String> {
+    let mut items: Vec<FitBond> = Vec::new();
+    let mut ytms: Vec<(f64, f64)> = Vec::new();
+    for (bond, settle_date, clean_price) in bonds {
+        let val = match bond.result(settle_date, *clean_price, Convention::Continuous) {
+            Some(val) => val,
+            None => continue,
+        };
+        let dirty_price = bond.dirty_price(settle_date, *clean_price);
+        let weight = match objective {
+            Objective::Price => 1.0,
+            Objective::Yield => 1.0 / (dirty_price * val.modd),
+        };
+        ytms.push((val.macd, val.ytm));
+        items.push(FitBond {
+            cfs: FitBond::cashflows(bond, ref_date, settle_date),
+            dirty_price,
+            weight,
+        });
+    }
+    let n_params = if svensson { 6 } else { 4 };
+    if items.len() < n_params {
+        return Err(format!(
+            "at least {} bonds with valid yields are required, but got {}",
+            n_params,
+            items.len()
+        ));
+    }
+    let sse = |x: &[f64]| {
+        let nss = Nss::from_params(x);
+        items
+            .iter()
+            .map(|item| ((nss.pv(&item.cfs) - item.dirty_price) * item.weight).powi(2))
+            .sum::<f64>()
+    };
+    // starts from the flat curve at the long end, sloping to the short end
+    ytms.sort_by(|a, b| a.0.total_cmp(&b.0));
+    let (short, long) = (ytms[0].1, ytms[ytms.len() - 1].1);
+    let mut guesses: Vec<Vec<f64>> = Vec::new();
+    for tau1 in TAU1_GUESSES {
+        if svensson {
+            for tau2 in TAU2_GUESSES {
+                guesses.push(vec![long, short - long, 0.0, tau1.ln(), 0.0, tau2.ln()]);
+            }
+        } else {
+            guesses.push(vec![long, short - long, 0.0, tau1.ln()]);
+        }
+    }
+    let best = guesses
+        .iter()
+        .map(|guess| {
+            // restarts once from the minimum, as the simplex may collapse prematurely
+            let x = nelder_mead(guess, 0.1, sse);
+            let x = nelder_mead(&x, 0.01, sse);
+            let fx = sse(&x);
+            (x, fx)
+        })
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .unwrap()
+        .0;
+    Ok(Nss::from_params(&best))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assert::NearEq;
+    fn from_ymd(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).expect("invalid or out-of-range date")
+    }
+    #[test]
+    fn nss_curve() {
+        let nss = Nss {
+            beta0: 0.04,
+            beta1: -0.02,
+            beta2: 0.01,
+            beta3: 0.0,
+            tau1: 2.0,
+            tau2: 2.0,
+        };
+        assert_near_eq!(nss.zero(0.0), 0.02);
+        assert!((nss.zero(1000.0) - 0.04).abs() < 1e-4);
+        let x: f64 = 0.5;
+        let expect =
+            0.04 - 0.02 * (1.0 - (-x).exp()) / x + 0.01 * ((1.0 - (-x).exp()) / x - (-x).exp());
+        assert_near_eq!(nss.zero(1.0), expect);
+    }
+    #[test]
+    fn fit_bonds() {
+        let ref_date = from_ymd(2021, 1, 1);
+        let nss = Nss {
+            beta0: 0.04,
+            beta1: -0.025,
+            beta2: 0.02,
+            beta3: -0.01,
+            tau1: 1.5,
+            tau2: 8.0,
+        };
+        let bonds: Vec<FixedBond> = [2, 3, 5, 7, 10, 15, 20, 30]
+            .iter()
+            .enumerate()
+            .map(|(i, years)| {
+                let cpn_rate = 0.02 + 0.002 * i as f64;
+                FixedBond::new(ref_date, from_ymd(2021 + years, 1, 1), 100.0, cpn_rate, 1).unwrap()
+            })
+            .collect();
+        let prices: Vec<(&FixedBond, NaiveDate, f64)> = bonds
+            .iter()
+            .map(|bond| (bond, ref_date, nss.dirty_price(bond, &ref_date, &ref_date)))
+            .collect();
+        for objective in [Objective::Price, Objective::Yield] {
+            let fitted = fit(&ref_date, &prices, true, objective).unwrap();
+            for (bond, _, price) in &prices {
+                let fitted_price = fitted.dirty_price(bond, &ref_date, &ref_date);
+                assert!((fitted_price - price).abs() < 1e-3);
+            }
+        }
+        // Nelson-Siegel can't fit the second hump exactly, but it's close
+        let fitted = fit(&ref_date, &prices, false, Objective::Yield).unwrap();
+        assert_eq!(fitted.beta3, 0.0);
+        for t in [2.0, 5.0, 10.0, 20.0] {
+            assert!((fitted.zero(t) - nss.zero(t)).abs() < 1e-3);
+        }
+        assert!(fit(&ref_date, &prices[..5], true, Objective::Price).is_err());
+        // the matured bond is left out
+        let matured =
+            FixedBond::new(from_ymd(2019, 1, 1), from_ymd(2020, 1, 1), 100.0, 0.02, 1).unwrap();
+        let mut with_matured = prices.clone();
+        with_matured.push((&matured, ref_date, 100.0));
+        let fitted = fit(&ref_date, &with_matured, true, Objective::Yield).unwrap();
+        for (bond, _, price) in &prices {
+            let fitted_price = fitted.dirty_price(bond, &ref_date, &ref_date);
+            assert!((fitted_price - price).abs() < 1e-3);
+        }
+        // 6 bonds, but only 5 with valid yields
+        assert!(fit(&ref_date, &with_matured[3..], true, Objective::Price).is_err());
+    }
+}
@@ -0,0 +1,86 @@
+const PRECISION: f64 = 1e-12;
+const MAX_ITERATION: u32 = 5000;
+
+// The minimum of `func` by the Nelder-Mead simplex method, starting from `x` with the initial
+// simplex of `step` along each axis. It stops when the function values on the simplex are
+// within `PRECISION`, or after `MAX_ITERATION` iterations.
+pub fn nelder_mead<F>(x: &[f64], step: f64, func: F) -> Vec<f64>
+where
+    F: Fn(&[f64]) -> f64,
+{
+    let n = x.len();
+    let mut simplex: Vec<(Vec<f64>, f64)> = (0..=n)
+        .map(|i| {
+            let mut v = x.to_vec();
+            if i > 0 {
+                v[i - 1] += step;
+            }
+            let fv = func(&v);
+            (v, fv)
+        })
+        .collect();
+    // moves `v` from the centroid `c` by the factor `k`, i.e., c + k * (v - c)
+    let towards = |c: &[f64], v: &[f64], k: f64| -> Vec<f64> {
+        c.iter().zip(v).map(|(c, v)| c + k * (v - c)).collect()
+    };
+    for _ in 0..MAX_ITERATION {
+        simplex.sort_by(|a, b| a.1.total_cmp(&b.1));
+        let (best, worst) = (simplex[0].1, simplex[n].1);
+        if (worst - best).abs() <= PRECISION * (1.0 + best.abs()) {
+            break;
+        }
+        let centroid: Vec<f64> = (0..n)
+            .map(|j| simplex[..n].iter().map(|v| v.0[j]).sum::<f64>() / n as f64)
+            .collect();
+        let reflected = towards(&centroid, &simplex[n].0, -1.0);
+        let fr = func(&reflected);
+        if fr < best {
+            let expanded = towards(&centroid, &simplex[n].0, -2.0);
+            let fe = func(&expanded);
+            simplex[n] = if fe < fr {
+                (expanded, fe)
+            } else {
+                (reflected, fr)
+            };
+        } else if fr < simplex[n - 1].1 {
+            simplex[n] = (reflected, fr);
+        } else {
+            // contracts inside, or outside if the reflected point is better than the worst
+            let k = if fr < worst { -0.5 } else { 0.5 };
+            let contracted = towards(&centroid, &simplex[n].0, k);
+            let fc = func(&contracted);
+            if fc < fr.min(worst) {
+                simplex[n] = (contracted, fc);
+            } else {
+                // shrinks towards the best vertex
+                let best = simplex[0].0.clone();
+                for vertex in simplex.iter_mut().skip(1) {
+                    let v = towards(&best, &vertex.0, 0.5);
+                    let fv = func(&v);
+                    *vertex = (v, fv);
+                }
+            }
+        }
+    }
+    simplex
+        .into_iter()
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .unwrap()
+        .0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn minimize() {
+        let quadratic = |x: &[f64]| (x[0] - 1.0).powi(2) + 2.0 * (x[1] + 0.5).powi(2);
+        let x = nelder_mead(&[0.0, 0.0], 0.1, quadratic);
+        assert!((x[0] - 1.0).abs() < 1e-5);
+        assert!((x[1] + 0.5).abs() < 1e-5);
+        let rosenbrock = |x: &[f64]| (1.0 - x[0]).powi(2) + 100.0 * (x[1] - x[0] * x[0]).powi(2);
+        let x = nelder_mead(&[-1.2, 1.0], 0.5, rosenbrock);
+        assert!((x[0] - 1.0).abs() < 1e-4);
+        assert!((x[1] - 1.0).abs() < 1e-4);
+    }
+}
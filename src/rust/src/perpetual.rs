@@ -0,0 +1,97 @@
+use crate::bond::FixedBond;
+use crate::date_handle;
+use chrono::NaiveDate;
+use std::collections::BTreeMap;
+
+// The perpetual bond, e.g., the bank perpetuals and the renewable corporate bonds, which the
+// issuer may call (at par) or extend on `first_call_date` and every `reset_months` after. Once
+// extended, the coupon resets to `benchmark` + `init_spread` + `step_up`, where `benchmark` is
+// the assumed benchmark yield on the reset dates.
+#[derive(Debug)]
+pub struct Perpetual {
+    pub value_date: NaiveDate,
+    pub first_call_date: NaiveDate,
+    pub redem_value: f64,
+    pub cpn_rate: f64,
+    pub cpn_freq: i32,
+    pub reset_months: i32,
+    pub benchmark: f64,
+    pub init_spread: f64,
+    pub step_up: f64,
+}
+
+impl Perpetual {
+    // The n-th call (and reset) date, starting from 0
+    fn call_date(&self, n: u32) -> NaiveDate {
+        date_handle::add_months(&self.first_call_date, self.reset_months * n as i32)
+    }
+    // The bond assuming the issuer extends it `extensions` times, i.e., it's redeemed on the
+    // (`extensions` + 1)-th call date. The earlier call dates remain in the call schedule.
+    pub fn to_bond(&self, extensions: u32) -> Result<FixedBond, String> {
+        if self.reset_months <= 0 {
+            return Err(format!(
+                "reset_months({}) must be positive",
+                self.reset_months
+            ));
+        }
+        let reset_rate = self.benchmark + self.init_spread + self.step_up;
+        let cpn_schedule: BTreeMap<NaiveDate, f64> = (0..extensions)
+            .map(|n| (self.call_date(n), reset_rate))
+            .collect();
+        let calls: BTreeMap<NaiveDate, f64> = (0..extensions)
+            .map(|n| (self.call_date(n), 100.0))
+            .collect();
+        FixedBond::new(
+            self.value_date,
+            self.call_date(extensions),
+            self.redem_value,
+            self.cpn_rate,
+            self.cpn_freq,
+        )?
+        .with_cpn_schedule(cpn_schedule)?
+        .with_calls(calls)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assert::NearEq;
+    use crate::bond::{BondCfType, Convention};
+    fn from_ymd(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).expect("invalid or out-of-range date")
+    }
+    #[test]
+    fn reset_and_extension() {
+        let perp = Perpetual {
+            value_date: from_ymd(2020, 1, 1),
+            first_call_date: from_ymd(2025, 1, 1),
+            redem_value: 100.0,
+            cpn_rate: 0.045,
+            cpn_freq: 1,
+            reset_months: 60,
+            benchmark: 0.03,
+            init_spread: 0.015,
+            step_up: 0.03,
+        };
+        // to the first call
+        let cf = perp.to_bond(0).unwrap().cashflow(BondCfType::All);
+        assert_eq!(cf.dates().last(), Some(&from_ymd(2025, 1, 1)));
+        assert_near_eq!(cf.values(), vec![4.5, 4.5, 4.5, 4.5, 104.5]);
+        // extended once, the coupon resets to 3% + 1.5% + 3%
+        let bond = perp.to_bond(1).unwrap();
+        let cf = bond.cashflow(BondCfType::All);
+        assert_eq!(cf.dates().last(), Some(&from_ymd(2030, 1, 1)));
+        assert_near_eq!(cf.values()[4], 4.5);
+        assert_near_eq!(cf.values()[5], 7.5);
+        assert_near_eq!(cf.values()[9], 107.5);
+        let ref_date = from_ymd(2021, 1, 1);
+        let (_, workout_date) = bond.ytw(&ref_date, 100.0, Convention::Annual).unwrap();
+        assert_eq!(workout_date, from_ymd(2025, 1, 1));
+        let perp = Perpetual {
+            reset_months: 0,
+            ..perp
+        };
+        assert!(perp.to_bond(1).is_err());
+    }
+}